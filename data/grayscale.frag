@@ -0,0 +1,15 @@
+#version 330 core
+
+in vec3 passed_Color;
+in vec2 passed_TextureCoordinates;
+
+out vec4 Color;
+
+uniform sampler2D ourTexture;
+
+void main()
+{
+    vec4 texel = texture(ourTexture, passed_TextureCoordinates);
+    float luminance = dot(texel.rgb, vec3(0.2126, 0.7152, 0.0722));
+    Color = vec4(vec3(luminance) * passed_Color, texel.a);
+}
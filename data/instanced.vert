@@ -0,0 +1,18 @@
+#version 330 core
+
+layout (location = 0) in vec3 Position;
+layout (location = 1) in vec3 Color;
+layout (location = 2) in vec2 TextureCoordinates;
+layout (location = 3) in mat4 InstanceTransform;
+
+out vec3 passed_Color;
+out vec2 passed_TextureCoordinates;
+
+uniform mat4 transform;
+
+void main()
+{
+    gl_Position = transform * InstanceTransform * vec4(Position, 1.0);
+    passed_Color = Color;
+    passed_TextureCoordinates = TextureCoordinates;
+}
@@ -0,0 +1,18 @@
+#version 330 core
+
+in vec3 passed_Color;
+in vec2 passed_TextureCoordinates;
+
+out vec4 Color;
+
+// Palette indices, stored in the red channel
+uniform sampler2D ourTexture;
+// One texel per color on the first row
+uniform sampler2D palette;
+
+void main()
+{
+    int index = int(texture(ourTexture, passed_TextureCoordinates).r * 255.0 + 0.5);
+    Color = texelFetch(palette, ivec2(index, 0), 0)
+        * vec4(passed_Color, 1.0);
+}
@@ -9,5 +9,6 @@ uniform sampler2D ourTexture;
 
 void main()
 {
-    Color = texture(ourTexture, passed_TextureCoordinates);
+    Color = texture(ourTexture, passed_TextureCoordinates)
+        * vec4(passed_Color, 1.0);
 }
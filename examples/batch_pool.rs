@@ -0,0 +1,104 @@
+/*
+* MIT License
+*
+* Copyright (c) 2018 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+//! Renders a sprite with a clip rectangle that moves every frame and checks
+//! that the batch pool doesn't keep one batch per clip rectangle
+
+mod common;
+
+use std::path::Path;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tuber_window_sdl2::SDLWindow;
+use tuber_graphics_opengl::{opengl, GLSceneRenderer};
+
+use tuber::window::Window;
+use tuber::resources::ResourceStore;
+use tuber::scene::{SceneGraph, SceneNode, NodeValue};
+use tuber::graphics::{scene_renderer::SceneRenderer, Sprite};
+use common::{FontStore, GLTextureStore, solid_texture};
+
+const FRAME_COUNT: i32 = 300;
+
+fn main() -> Result<(), String> {
+    // Setup SDL
+    let sdl_context = sdl2::init()?;
+    let sdl_video_subsystem = sdl_context.video()?;
+    let sdl_event_pump = Rc::new(RefCell::new(sdl_context.event_pump()?));
+
+    // Setup SDL GL context
+    let sdl_gl_attributes = sdl_video_subsystem.gl_attr();
+    sdl_gl_attributes.set_context_profile(sdl2::video::GLProfile::Core);
+    sdl_gl_attributes.set_context_version(3, 3);
+
+    // Create window
+    let mut window = SDLWindow::new(&sdl_video_subsystem,
+                                    sdl_event_pump.clone());
+    // Load gl functions
+    opengl::load_symbols(|s| sdl_video_subsystem.gl_get_proc_address(s)
+        as *const std::os::raw::c_void);
+
+    // Shader loading
+    let vertex_shader = opengl::Shader::from_file(
+        Path::new("data/textured.vert"),
+        gl::VERTEX_SHADER)?;
+    let fragment_shader = opengl::Shader::from_file(
+        Path::new("data/textured.frag"),
+        gl::FRAGMENT_SHADER)?;
+
+    let shader_program = opengl::ShaderProgram::from_shaders(
+        &[vertex_shader, fragment_shader]
+    )?;
+    shader_program.use_program();
+
+    let texture_store = Rc::new(RefCell::new(GLTextureStore::new()));
+    texture_store.borrow_mut().store("red".into(), solid_texture([255, 0, 0, 255]));
+    let font_store = Rc::new(RefCell::new(FontStore::new()));
+
+    let mut scene = SceneGraph::new();
+    scene.root_mut().add_child(SceneNode::new("sprite", NodeValue::SpriteNode(
+            Sprite::new(800.0, 600.0, "red".into()))));
+
+    let mut scene_renderer = GLSceneRenderer::new(texture_store.clone(), font_store.clone());
+    scene_renderer.set_viewport(0, 0, 800, 600);
+    scene_renderer.set_projection(GLSceneRenderer::screen_ortho(800.0, 600.0));
+
+    // Each clip rectangle makes distinct mesh attributes, so a new batch
+    let mut max_pool_size = 0;
+    for frame in 0..FRAME_COUNT {
+        scene_renderer.set_node_clip("sprite", (frame, 0, 400, 400));
+        scene_renderer.render_scene(&scene);
+        window.display();
+        max_pool_size = max_pool_size.max(scene_renderer.batch_pool_size());
+    }
+
+    if max_pool_size > GLSceneRenderer::BATCH_POOL_CAPACITY {
+        return Err(format!("The batch pool grew to {} batches over {} frames",
+                           max_pool_size, FRAME_COUNT));
+    }
+
+    println!("The batch pool kept at most {} batches", max_pool_size);
+    Ok(())
+}
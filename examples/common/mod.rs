@@ -0,0 +1,107 @@
+/*
+* MIT License
+*
+* Copyright (c) 2018 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+//! Resource stores and helpers shared by the examples
+//!
+//! Each example only uses some of the helpers.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use tuber_graphics_opengl::{opengl, font::Font};
+
+pub struct FontStore {
+    fonts: HashMap<String, Font>
+}
+
+impl FontStore {
+    pub fn new() -> FontStore {
+        FontStore {
+            fonts: HashMap::new()
+        }
+    }
+}
+
+impl tuber::resources::ResourceStore<Font> for FontStore {
+    fn store(&mut self, resource_file_path: String, value: Font) {
+        self.fonts.insert(resource_file_path, value);
+    }
+    fn remove(&mut self, resource_file_path: &str) {
+        self.fonts.remove(resource_file_path);
+    }
+
+    fn get(&self, resource_file_path: &str) -> Option<&Font> {
+        self.fonts.get(resource_file_path)
+    }
+    fn get_mut(&mut self, resource_file_path: &str) -> Option<&mut Font> {
+        self.fonts.get_mut(resource_file_path)
+    }
+}
+
+pub struct GLTextureStore {
+    textures: HashMap<String, opengl::Texture>
+}
+
+impl GLTextureStore {
+    pub fn new() -> GLTextureStore {
+        GLTextureStore {
+            textures: HashMap::new()
+        }
+    }
+}
+
+impl tuber::resources::ResourceStore<opengl::Texture> for GLTextureStore {
+    fn store(&mut self, resource_file_path: String, value: opengl::Texture) {
+        self.textures.insert(resource_file_path, value);
+    }
+    fn remove(&mut self, resource_file_path: &str) {
+        self.textures.remove(resource_file_path);
+    }
+
+    fn get(&self, resource_file_path: &str) -> Option<&opengl::Texture> {
+        self.textures.get(resource_file_path)
+    }
+    fn get_mut(&mut self, resource_file_path: &str) -> Option<&mut opengl::Texture> {
+        self.textures.get_mut(resource_file_path)
+    }
+}
+
+/// Creates a 1x1 texture of the given color
+pub fn solid_texture(color: [u8; 4]) -> opengl::Texture {
+    let image = opengl::DecodedImage::new(1, 1, gl::RGBA, color.to_vec());
+    opengl::Texture::from_image(&image, &opengl::TextureParameters::new())
+}
+
+/// Checks the color of a pixel of an 800x600 window, given from the
+/// top-left corner
+pub fn expect_color(x: f32, y: f32, expected_color: &[u8]) -> Result<(), String> {
+    // Framebuffer rows start at the bottom
+    let pixel = opengl::read_pixels(x as i32, 600 - y as i32, 1, 1);
+    if &pixel[0..3] != expected_color {
+        return Err(format!("Expected the color {:?} at ({}, {}), found {:?}",
+                           expected_color, x, y, &pixel[0..3]));
+    }
+
+    Ok(())
+}
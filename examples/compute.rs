@@ -0,0 +1,98 @@
+/*
+* MIT License
+*
+* Copyright (c) 2018 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+
+//! Links a compute-only shader program, dispatches it and checks the
+//! values it wrote to a buffer
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tuber_window_sdl2::SDLWindow;
+use tuber_graphics_opengl::opengl;
+
+const COMPUTE_SHADER: &str = "#version 430 core
+layout (local_size_x = 64) in;
+
+layout (std430, binding = 0) buffer Values {
+    uint values[];
+};
+
+void main()
+{
+    uint index = gl_GlobalInvocationID.x;
+    values[index] = index * 2u;
+}
+";
+
+const VALUE_COUNT: usize = 16 * 64;
+
+fn main() -> Result<(), String> {
+    // Setup SDL
+    let sdl_context = sdl2::init()?;
+    let sdl_video_subsystem = sdl_context.video()?;
+    let sdl_event_pump = Rc::new(RefCell::new(sdl_context.event_pump()?));
+
+    // Setup SDL GL context
+    let sdl_gl_attributes = sdl_video_subsystem.gl_attr();
+    sdl_gl_attributes.set_context_profile(sdl2::video::GLProfile::Core);
+    sdl_gl_attributes.set_context_version(4, 3);
+
+    // Create window
+    let _window = SDLWindow::new(&sdl_video_subsystem,
+                                 sdl_event_pump.clone());
+    // Load gl functions
+    opengl::load_symbols(|s| sdl_video_subsystem.gl_get_proc_address(s)
+        as *const std::os::raw::c_void);
+
+    if !opengl::supports_compute_shaders() {
+        return Err("Compute shaders aren't supported by this context".into());
+    }
+
+    let compute_shader = opengl::Shader::from_source(COMPUTE_SHADER,
+                                                     gl::COMPUTE_SHADER)?;
+    let compute_program = opengl::ShaderProgram::from_shaders(&[compute_shader])?;
+
+    let values = opengl::BufferObject::with_size(gl::SHADER_STORAGE_BUFFER,
+                                                 VALUE_COUNT * std::mem::size_of::<u32>());
+    values.bind_base(0);
+
+    compute_program.use_program();
+    opengl::dispatch_compute((VALUE_COUNT / 64) as u32, 1, 1)?;
+    opengl::memory_barrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+
+    let written_values: Vec<u32> = values.read_to_vec(VALUE_COUNT)?;
+    if written_values.len() != VALUE_COUNT {
+        return Err("Couldn't read the values back".into());
+    }
+
+    for (index, value) in written_values.iter().enumerate() {
+        if *value != index as u32 * 2 {
+            return Err(format!("Expected {} at index {}, found {}", index * 2, index, value));
+        }
+    }
+
+    println!("Compute program linked and dispatched, {} values written", VALUE_COUNT);
+    Ok(())
+}
@@ -31,6 +31,7 @@ use tuber::input::keyboard;
 
 use tuber_window_sdl2::SDLWindow;
 use tuber_graphics_opengl::{opengl, GLSceneRenderer, font::Font};
+use tuber_graphics_opengl::shader::{ShaderLoader, ShaderStore};
 
 use tuber::resources::{ResourceLoader, ResourceStore};
 use tuber::scene::{SceneGraph, SceneNode, NodeValue};
@@ -56,21 +57,20 @@ fn main() -> Result<(), String> {
 
     
     // Shader loading
-    let vertex_shader = opengl::Shader::from_file(
-        Path::new("data/textured.vert"), 
-        gl::VERTEX_SHADER)?;
-    let fragment_shader = opengl::Shader::from_file(
-        Path::new("data/textured.frag"),
-        gl::FRAGMENT_SHADER)?;
-
-    let mut shader_program = opengl::ShaderProgram::from_shaders(
-        &[vertex_shader, fragment_shader]
-    )?;
-
-    shader_program.use_program();
-    shader_program.set_uniform_mat4("transform", nalgebra_glm::identity());
+    let mut shader_loader = ShaderLoader::new("data");
+    let shader_store = Rc::new(RefCell::new(ShaderStore::new()));
+    shader_store.borrow_mut().store("textured_shader".into(),
+                                    shader_loader.load("textured_shader")?);
+
+    {
+        let mut shader_store = shader_store.borrow_mut();
+        let shader_program = shader_store.get_mut("textured_shader").unwrap();
+        shader_program.use_program();
+        shader_program.set_uniform_mat4("transform", nalgebra_glm::identity());
+    }
 
-    let mut texture_loader = GLTextureLoader{};
+    let mut texture_loader = GLTextureLoader::with_parameters(
+        opengl::TextureParameters::pixel_art());
     let texture = texture_loader.load("64x64")
         .expect("Couldn't load texture");
     let texture2 = texture_loader.load("64x64b")
@@ -175,55 +175,59 @@ impl tuber::resources::ResourceStore<opengl::Texture> for GLTextureStore {
 
 }
 
-struct GLTextureLoader;
+struct GLTextureLoader {
+    parameters: opengl::TextureParameters
+}
+
 impl GLTextureLoader {
-    fn load_texture(&mut self, texture_file_path: String) 
-        -> Result<opengl::Texture, String> {
+    /// Creates a loader uploading the textures with the given parameters
+    pub fn with_parameters(parameters: opengl::TextureParameters) -> GLTextureLoader {
+        GLTextureLoader {
+            parameters
+        }
+    }
+
+    /// Decodes an image file
+    ///
+    /// This doesn't use OpenGL and can run on any thread
+    pub fn decode(texture_file_path: &str) -> Result<opengl::DecodedImage, String> {
         use sdl2::image::LoadSurface;
         use sdl2::surface::Surface;
 
-        let surface = Surface::from_file(Path::new(&texture_file_path))?;
-        let texture = opengl::Texture::new(gl::TEXTURE_2D);
+        let surface = Surface::from_file(Path::new(texture_file_path))?;
+        let format = match surface.pixel_format_enum().byte_size_per_pixel() {
+            4 => gl::RGBA,
+            1 => gl::RED,
+            _ => gl::RGB
+        };
 
-
-        let mut mode = gl::RGB;
         let height = surface.height() as usize;
-        let bytes_per_pixel = surface.pixel_format_enum().byte_size_per_pixel();
-        if bytes_per_pixel == 4 {
-            mode = gl::RGBA;
-        }
-
         let bytes_per_row = surface.pitch() as usize;
+        let pixels = surface.without_lock()
+            .ok_or("Can't access the image pixels")?;
         let mut flipped_image: Vec<u8> = Vec::with_capacity(bytes_per_row * height);
-        let image_data_ptr = surface.without_lock().unwrap().as_ptr();
-        unsafe {
-            for i in 0..height {
-                image_data_ptr.offset((bytes_per_row * (height - i - 1)) as isize)
-                    .copy_to(flipped_image.as_mut_ptr().offset((i * bytes_per_row) as isize), 
-                             bytes_per_row);
-            }
+        for i in 0..height {
+            let row_start = bytes_per_row * (height - i - 1);
+            flipped_image.extend_from_slice(&pixels[row_start..row_start + bytes_per_row]);
         }
 
-        texture.bind();
-        texture.set_2d_image_data(0,
-                                  mode as gl::types::GLint,
-                                  surface.width() as gl::types::GLint,
-                                  surface.height() as gl::types::GLint,
-                                  0,
-                                  mode,
-                                  gl::UNSIGNED_BYTE,
-                                  flipped_image.as_ptr() as *const gl::types::GLvoid);
-        texture.generate_mipmap();
-        texture.set_int_parameter(gl::TEXTURE_MIN_FILTER, 
-                                  gl::NEAREST as gl::types::GLint);
-        texture.set_int_parameter(gl::TEXTURE_MAG_FILTER, 
-                                  gl::NEAREST as gl::types::GLint);
-        texture.set_int_parameter(gl::TEXTURE_WRAP_S, 
-                                  gl::REPEAT as gl::types::GLint);
-        texture.set_int_parameter(gl::TEXTURE_WRAP_T, 
-                                  gl::REPEAT as gl::types::GLint);
-
-        Ok(texture)
+        Ok(opengl::DecodedImage::new(surface.width() as gl::types::GLsizei,
+                                     surface.height() as gl::types::GLsizei,
+                                     format,
+                                     flipped_image))
+    }
+
+    /// Uploads a decoded image to a new texture
+    ///
+    /// This must run on the thread owning the OpenGL context
+    pub fn upload(&self, image: &opengl::DecodedImage) -> opengl::Texture {
+        opengl::Texture::from_image(image, &self.parameters)
+    }
+
+    fn load_texture(&mut self, texture_file_path: String) 
+        -> Result<opengl::Texture, String> {
+        let image = GLTextureLoader::decode(&texture_file_path)?;
+        Ok(self.upload(&image))
     }
 }
 
@@ -23,21 +23,20 @@
 */
 
 use std::path::Path;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use tuber::window::{Window, WindowEvent};
 use tuber::input::keyboard;
 
 use tuber_window_sdl2::SDLWindow;
-use tuber_graphics_opengl::{opengl, GLSceneRenderer, font::*};
+use tuber_graphics_opengl::{opengl, GLSceneRenderer, TextureFilter, font::*};
 
 use tuber::resources::{ResourceLoader, ResourceStore};
 use tuber::scene::{SceneGraph, SceneNode, NodeValue};
 use tuber::graphics::{scene_renderer::SceneRenderer, Text};
 
 use bmfont_parser::{BMFont, Format};
-use nalgebra_glm;
 
 fn main() -> Result<(), String> {
     // Setup SDL
@@ -56,6 +55,9 @@ fn main() -> Result<(), String> {
     // Load gl functions
     opengl::load_symbols(|s| sdl_video_subsystem.gl_get_proc_address(s)
         as *const std::os::raw::c_void);
+    opengl::enable_debug_output(|source, message_type, id, severity, message| {
+        eprintln!("{:?} {:?} {} {:?}: {}", source, message_type, id, severity, message);
+    });
 
     
     // Shader loading
@@ -70,14 +72,17 @@ fn main() -> Result<(), String> {
         &[vertex_shader, fragment_shader]
     )?;
 
-    let transform = nalgebra_glm::ortho(0.0, 800.0, 600.0, 0.0, 0.0, 100.0);
+    let transform = GLSceneRenderer::screen_ortho(800.0, 600.0);
     shader_program.use_program();
     shader_program.set_uniform_mat4("transform", transform);
     let texture_store = Rc::new(RefCell::new(GLTextureStore::new()));
 
     let font_store = Rc::new(RefCell::new(FontStore::new()));
+    let mut scene_renderer = GLSceneRenderer::new(texture_store.clone(), font_store.clone());
+    scene_renderer.set_default_texture_filter(gl::LINEAR, gl::LINEAR);
+
     use tuber::resources::ResourceLoader;
-    let mut font_loader = BitmapFontLoader::new();
+    let mut font_loader = BitmapFontLoader::new(scene_renderer.default_texture_filter());
     font_store.borrow_mut().store("default_font2".into(), font_loader.load("default_font2")?);
 
     
@@ -86,9 +91,9 @@ fn main() -> Result<(), String> {
             Text::new("CAELI 音楽".into(), "default_font2".into())));
     scene.root_mut().add_child(text);
 
-    let mut scene_renderer = GLSceneRenderer::new(texture_store.clone(), font_store.clone());
     scene_renderer.set_clear_color((1.0, 0.0, 0.0));
     scene_renderer.set_viewport(0, 0, 800, 600);
+    scene_renderer.set_projection(transform);
 
     'main_loop: loop {
         for event in window.poll_event() {
@@ -139,9 +144,9 @@ struct BitmapFontLoader {
 }
 
 impl BitmapFontLoader {
-    pub fn new() -> BitmapFontLoader {
+    pub fn new(default_filter: Rc<Cell<TextureFilter>>) -> BitmapFontLoader {
         BitmapFontLoader {
-            texture_loader: GLTextureLoader::new()
+            texture_loader: GLTextureLoader::new(default_filter)
         }
     }
 
@@ -156,14 +161,23 @@ impl BitmapFontLoader {
         let common_details = bmfont.common_details;
 
 
-        let (horizontal_scale, vertical_scale) = if let Some(details) = common_details {
-            (details.scale_w as f32, details.scale_h as f32)
+        let details = if let Some(details) = common_details {
+            details
         } else {
             return Err("Font scale not found".into());
         };
+        let horizontal_scale = details.scale_w as f32;
+        let vertical_scale = details.scale_h as f32;
 
         let texture = self.texture_loader.load_texture(page.image_path.to_str().unwrap())?;
+        if texture.format() == gl::RED {
+            // Single-channel atlases store the glyph coverage
+            texture.bind();
+            texture.set_swizzle([gl::ONE, gl::ONE, gl::ONE, gl::RED]);
+        }
+
         let mut font = Font::new(texture, horizontal_scale, vertical_scale);
+        font.set_metrics(details.line_height as f32, details.base as f32);
 
         for (char_id, character) in bmfont.chars {
             let character_metadata = FontCharacter::new(
@@ -234,59 +248,67 @@ impl tuber::resources::ResourceStore<opengl::Texture> for GLTextureStore {
     }
 }
 
-struct GLTextureLoader;
+struct GLTextureLoader {
+    default_filter: Rc<Cell<TextureFilter>>
+}
+
 impl GLTextureLoader {
-    pub fn new() -> GLTextureLoader {
-        GLTextureLoader
+    pub fn new(default_filter: Rc<Cell<TextureFilter>>) -> GLTextureLoader {
+        GLTextureLoader {
+            default_filter
+        }
     }
 
-    pub fn load_texture(&mut self, texture_file_path: &str)
-        -> Result<opengl::Texture, String> {
+    /// Returns the parameters of the next textures, with the renderer's
+    /// default filters
+    fn parameters(&self) -> opengl::TextureParameters {
+        let (min_filter, mag_filter) = self.default_filter.get();
+        opengl::TextureParameters::new()
+            .min_filter(min_filter)
+            .mag_filter(mag_filter)
+    }
+
+    /// Decodes an image file
+    ///
+    /// This doesn't use OpenGL and can run on any thread
+    pub fn decode(texture_file_path: &str) -> Result<opengl::DecodedImage, String> {
         use sdl2::image::LoadSurface;
         use sdl2::surface::Surface;
 
-        let surface = Surface::from_file(Path::new(&texture_file_path))?;
-        let texture = opengl::Texture::new(gl::TEXTURE_2D);
-
+        let surface = Surface::from_file(Path::new(texture_file_path))?;
+        let format = match surface.pixel_format_enum().byte_size_per_pixel() {
+            4 => gl::RGBA,
+            1 => gl::RED,
+            _ => gl::RGB
+        };
 
-        let mut mode = gl::RGB;
         let height = surface.height() as usize;
-        let bytes_per_pixel = surface.pixel_format_enum().byte_size_per_pixel();
-        if bytes_per_pixel == 4 {
-            mode = gl::RGBA;
-        }
-
         let bytes_per_row = surface.pitch() as usize;
+        let pixels = surface.without_lock()
+            .ok_or("Can't access the image pixels")?;
         let mut flipped_image: Vec<u8> = Vec::with_capacity(bytes_per_row * height);
-        let image_data_ptr = surface.without_lock().unwrap().as_ptr();
-        unsafe {
-            for i in 0..height {
-                image_data_ptr.offset((bytes_per_row * (height - i - 1)) as isize)
-                    .copy_to(flipped_image.as_mut_ptr().offset((i * bytes_per_row) as isize), 
-                             bytes_per_row);
-            }
+        for i in 0..height {
+            let row_start = bytes_per_row * (height - i - 1);
+            flipped_image.extend_from_slice(&pixels[row_start..row_start + bytes_per_row]);
         }
 
-        texture.bind();
-        texture.set_2d_image_data(0,
-                                  mode as gl::types::GLint,
-                                  surface.width() as gl::types::GLint,
-                                  surface.height() as gl::types::GLint,
-                                  0,
-                                  mode,
-                                  gl::UNSIGNED_BYTE,
-                                  flipped_image.as_ptr() as *const gl::types::GLvoid);
-        texture.generate_mipmap();
-        texture.set_int_parameter(gl::TEXTURE_MIN_FILTER, 
-                                  gl::NEAREST as gl::types::GLint);
-        texture.set_int_parameter(gl::TEXTURE_MAG_FILTER, 
-                                  gl::NEAREST as gl::types::GLint);
-        texture.set_int_parameter(gl::TEXTURE_WRAP_S, 
-                                  gl::REPEAT as gl::types::GLint);
-        texture.set_int_parameter(gl::TEXTURE_WRAP_T, 
-                                  gl::REPEAT as gl::types::GLint);
-
-        Ok(texture)
+        Ok(opengl::DecodedImage::new(surface.width() as gl::types::GLsizei,
+                                     surface.height() as gl::types::GLsizei,
+                                     format,
+                                     flipped_image))
+    }
+
+    /// Uploads a decoded image to a new texture
+    ///
+    /// This must run on the thread owning the OpenGL context
+    pub fn upload(&self, image: &opengl::DecodedImage) -> opengl::Texture {
+        opengl::Texture::from_image(image, &self.parameters())
+    }
+
+    pub fn load_texture(&mut self, texture_file_path: &str)
+        -> Result<opengl::Texture, String> {
+        let image = GLTextureLoader::decode(texture_file_path)?;
+        Ok(self.upload(&image))
     }
 }
 
@@ -0,0 +1,123 @@
+/*
+* MIT License
+*
+* Copyright (c) 2018 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+
+//! Renders an anti-aliased white glyph over a red background and checks
+//! that its edges don't darken the background
+
+mod common;
+
+use std::path::Path;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tuber_window_sdl2::SDLWindow;
+use tuber_graphics_opengl::{opengl, GLSceneRenderer, font::*};
+
+use tuber::window::Window;
+use tuber::resources::ResourceStore;
+use tuber::scene::{SceneGraph, SceneNode, NodeValue};
+use tuber::graphics::{scene_renderer::SceneRenderer, Text};
+use common::{FontStore, GLTextureStore};
+
+const GLYPH_SIZE: i32 = 16;
+
+fn main() -> Result<(), String> {
+    // Setup SDL
+    let sdl_context = sdl2::init()?;
+    let sdl_video_subsystem = sdl_context.video()?;
+    let sdl_event_pump = Rc::new(RefCell::new(sdl_context.event_pump()?));
+
+    // Setup SDL GL context
+    let sdl_gl_attributes = sdl_video_subsystem.gl_attr();
+    sdl_gl_attributes.set_context_profile(sdl2::video::GLProfile::Core);
+    sdl_gl_attributes.set_context_version(3, 3);
+
+    // Create window
+    let mut window = SDLWindow::new(&sdl_video_subsystem,
+                                    sdl_event_pump.clone());
+    // Load gl functions
+    opengl::load_symbols(|s| sdl_video_subsystem.gl_get_proc_address(s)
+        as *const std::os::raw::c_void);
+
+    // Shader loading
+    let vertex_shader = opengl::Shader::from_file(
+        Path::new("data/textured.vert"),
+        gl::VERTEX_SHADER)?;
+    let fragment_shader = opengl::Shader::from_file(
+        Path::new("data/textured.frag"),
+        gl::FRAGMENT_SHADER)?;
+
+    let mut shader_program = opengl::ShaderProgram::from_shaders(
+        &[vertex_shader, fragment_shader]
+    )?;
+
+    let transform = GLSceneRenderer::screen_ortho(800.0, 600.0);
+    shader_program.use_program();
+    shader_program.set_uniform_mat4("transform", transform);
+
+    // The glyph coverage ramps from transparent to opaque horizontally
+    let coverage = (0..GLYPH_SIZE * GLYPH_SIZE)
+        .map(|i| ((i % GLYPH_SIZE) * 255 / (GLYPH_SIZE - 1)) as u8)
+        .collect();
+    let image = opengl::DecodedImage::new(GLYPH_SIZE, GLYPH_SIZE, gl::RED, coverage);
+    let texture = opengl::Texture::from_image(&image, &opengl::TextureParameters::new());
+    texture.set_swizzle([gl::ONE, gl::ONE, gl::ONE, gl::RED]);
+
+    let size = GLYPH_SIZE as f32;
+    let mut font = Font::new(texture, size, size);
+    font.add_character('a', FontCharacter::new(0.0, 0.0, size, size, 0.0, 0.0, size));
+
+    let font_store = Rc::new(RefCell::new(FontStore::new()));
+    font_store.borrow_mut().store("ramp_font".into(), font);
+    let texture_store = Rc::new(RefCell::new(GLTextureStore::new()));
+
+    let mut scene = SceneGraph::new();
+    let text = SceneNode::new("glyph", NodeValue::TextNode(
+            Text::new("a".into(), "ramp_font".into())));
+    scene.root_mut().add_child(text);
+
+    let mut scene_renderer = GLSceneRenderer::new(texture_store.clone(), font_store.clone());
+    scene_renderer.set_clear_color((1.0, 0.0, 0.0));
+    scene_renderer.set_viewport(0, 0, 800, 600);
+    scene_renderer.set_projection(transform);
+    scene_renderer.render_scene(&scene);
+
+    // The glyph is in the top-left corner, framebuffer rows start at the bottom
+    let pixels = opengl::read_pixels(0, 600 - 2 * GLYPH_SIZE,
+                                     2 * GLYPH_SIZE, 2 * GLYPH_SIZE);
+    let red_min = pixels.chunks(4).map(|pixel| pixel[0]).min().unwrap_or(0);
+    let green_max = pixels.chunks(4).map(|pixel| pixel[1]).max().unwrap_or(0);
+    window.display();
+
+    if green_max < 200 {
+        return Err("The glyph hasn't been drawn".into());
+    }
+    if red_min < 250 {
+        return Err(format!("The glyph edges darken the background (red down to {})", red_min));
+    }
+
+    println!("No dark halo around the glyph");
+    Ok(())
+}
@@ -0,0 +1,157 @@
+/*
+* MIT License
+*
+* Copyright (c) 2018 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+//! Links a program with a passthrough geometry shader expanding points
+//! into quads, draws a point and checks that a quad covers it
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tuber_window_sdl2::SDLWindow;
+use tuber_graphics_opengl::opengl;
+
+const VERTEX_SHADER: &str = "#version 330 core
+layout (location = 0) in vec3 Position;
+
+void main()
+{
+    gl_Position = vec4(Position, 1.0);
+}
+";
+
+const GEOMETRY_SHADER: &str = "#version 330 core
+layout (points) in;
+layout (triangle_strip, max_vertices = 4) out;
+
+uniform float half_size;
+
+void main()
+{
+    vec4 center = gl_in[0].gl_Position;
+    gl_Position = center + vec4(-half_size, -half_size, 0.0, 0.0);
+    EmitVertex();
+    gl_Position = center + vec4(half_size, -half_size, 0.0, 0.0);
+    EmitVertex();
+    gl_Position = center + vec4(-half_size, half_size, 0.0, 0.0);
+    EmitVertex();
+    gl_Position = center + vec4(half_size, half_size, 0.0, 0.0);
+    EmitVertex();
+    EndPrimitive();
+}
+";
+
+const FRAGMENT_SHADER: &str = "#version 330 core
+out vec4 FragColor;
+
+// Declared but unused, so the compiler strips it
+uniform float intensity;
+
+void main()
+{
+    FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+}
+";
+
+fn main() -> Result<(), String> {
+    // Setup SDL
+    let sdl_context = sdl2::init()?;
+    let sdl_video_subsystem = sdl_context.video()?;
+    let sdl_event_pump = Rc::new(RefCell::new(sdl_context.event_pump()?));
+
+    // Setup SDL GL context
+    let sdl_gl_attributes = sdl_video_subsystem.gl_attr();
+    sdl_gl_attributes.set_context_profile(sdl2::video::GLProfile::Core);
+    sdl_gl_attributes.set_context_version(3, 3);
+
+    // Create window
+    let _window = SDLWindow::new(&sdl_video_subsystem,
+                                 sdl_event_pump.clone());
+    // Load gl functions
+    opengl::load_symbols(|s| sdl_video_subsystem.gl_get_proc_address(s)
+        as *const std::os::raw::c_void);
+
+    let shaders = [
+        opengl::Shader::from_source(VERTEX_SHADER, gl::VERTEX_SHADER)?,
+        opengl::Shader::from_source(GEOMETRY_SHADER, gl::GEOMETRY_SHADER)?,
+        opengl::Shader::from_source(FRAGMENT_SHADER, gl::FRAGMENT_SHADER)?
+    ];
+    let shader_program = opengl::ShaderProgram::from_shaders(&shaders)?;
+    shader_program.use_program();
+    shader_program.try_set_uniform_value("half_size", 0.1f32)?;
+
+    // The cached location must be the one the program reports
+    let location = shader_program.uniform_location("half_size");
+    let uniform_name = std::ffi::CString::new("half_size").unwrap();
+    let program_location = unsafe {
+        gl::GetUniformLocation(shader_program.identifier(), uniform_name.as_ptr())
+    };
+    let active = shader_program.active_uniforms();
+    if location == -1 || location != program_location
+        || !active.iter().any(|(name, kind, _)| name == "half_size" && *kind == gl::FLOAT) {
+        return Err(format!("half_size resolved to {} instead of {} in {:?}",
+                           location, program_location, active));
+    }
+
+    match shader_program.try_set_uniform_value("intensity", 1.0f32) {
+        Err(ref error) if error.contains("optimized out") => {},
+        result => return Err(format!("Expected intensity to be optimized out, got {:?}", result))
+    }
+    match shader_program.try_set_uniform_value("half_sise", 0.1f32) {
+        Err(ref error) if error.contains("isn't declared") => {},
+        result => return Err(format!("Expected half_sise to be undeclared, got {:?}", result))
+    }
+    // An input of the vertex shader, not a uniform
+    match shader_program.try_set_uniform_value("Position", 0.1f32) {
+        Err(ref error) if error.contains("isn't declared") => {},
+        result => return Err(format!("Expected Position to be undeclared, got {:?}", result))
+    }
+
+    let point: [f32; 3] = [0.0, 0.0, 0.0];
+    let vao = opengl::VertexArrayObject::new();
+    let vbo = opengl::BufferObject::new(gl::ARRAY_BUFFER);
+    vao.bind();
+    vbo.bind();
+    vbo.set_data(std::mem::size_of_val(&point),
+                 point.as_ptr() as *const gl::types::GLvoid,
+                 gl::STATIC_DRAW);
+    vao.configure(&opengl::VertexLayout::new().attribute(3, gl::FLOAT, gl::FALSE));
+
+    opengl::set_viewport(0, 0, 800, 600);
+    opengl::set_clear_color(0.0, 0.0, 0.0);
+    opengl::clear(gl::COLOR_BUFFER_BIT);
+    opengl::draw_arrays(gl::POINTS, 0, 1);
+    vao.unbind();
+
+    // The quad spans 10% of the viewport on each side of the center
+    for &(x, y, expected) in [(400, 300, 255), (420, 300, 255), (500, 300, 0)].iter() {
+        let pixel = opengl::read_pixels(x, y, 1, 1);
+        if pixel[0] != expected {
+            return Err(format!("Expected {} at ({}, {}), found {}", expected, x, y, pixel[0]));
+        }
+    }
+
+    println!("Geometry shader linked, the point was expanded into a quad (up to {} output vertices)",
+             opengl::max_geometry_output_vertices());
+    Ok(())
+}
@@ -86,17 +86,7 @@ fn main() -> Result<(), String> {
 
     vao.bind();
     vbo.bind();
-    vao.set_attribute(0, 3, gl::FLOAT, gl::FALSE,
-                      std::mem::size_of::<Vertex>(),
-                      std::ptr::null() as *const gl::types::GLvoid);
-    vao.set_attribute(1, 3, gl::FLOAT, gl::FALSE,
-                      std::mem::size_of::<Vertex>(),
-                      (3 * std::mem::size_of::<f32>())
-                      as *const gl::types::GLvoid);
-    vao.set_attribute(2, 2, gl::FLOAT, gl::FALSE,
-                      std::mem::size_of::<Vertex>(),
-                      (6 * std::mem::size_of::<f32>())
-                      as *const gl::types::GLvoid);
+    vao.configure(&Vertex::layout());
     vao.unbind();
 
 
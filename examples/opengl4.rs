@@ -101,17 +101,7 @@ fn main() -> Result<(), String> {
     vao.bind();
     vbo.bind();
     ebo.bind();
-    vao.set_attribute(0, 3, gl::FLOAT, gl::FALSE,
-                      std::mem::size_of::<Vertex>(),
-                      std::ptr::null() as *const gl::types::GLvoid);
-    vao.set_attribute(1, 3, gl::FLOAT, gl::FALSE,
-                      std::mem::size_of::<Vertex>(),
-                      (3 * std::mem::size_of::<f32>())
-                      as *const gl::types::GLvoid);
-    vao.set_attribute(2, 2, gl::FLOAT, gl::FALSE,
-                      std::mem::size_of::<Vertex>(),
-                      (6 * std::mem::size_of::<f32>())
-                      as *const gl::types::GLvoid);
+    vao.configure(&Vertex::layout());
     vao.unbind();
 
     let texture = load_texture()
@@ -177,15 +167,7 @@ fn load_texture() -> Result<opengl::Texture, String> {
                               mode,
                               gl::UNSIGNED_BYTE,
                               flipped_image.as_ptr() as *const gl::types::GLvoid);
-    texture.generate_mipmap();
-    texture.set_int_parameter(gl::TEXTURE_MIN_FILTER, 
-                              gl::NEAREST as gl::types::GLint);
-    texture.set_int_parameter(gl::TEXTURE_MAG_FILTER, 
-                              gl::NEAREST as gl::types::GLint);
-    texture.set_int_parameter(gl::TEXTURE_WRAP_S, 
-                              gl::REPEAT as gl::types::GLint);
-    texture.set_int_parameter(gl::TEXTURE_WRAP_T, 
-                              gl::REPEAT as gl::types::GLint);
+    opengl::TextureParameters::new().apply(&texture);
 
     Ok(texture)
 }
@@ -0,0 +1,145 @@
+/*
+* MIT License
+*
+* Copyright (c) 2018 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+//! Renders overlapping meshes and checks that they are drawn in the
+//! expected order
+
+mod common;
+
+use std::path::Path;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tuber_window_sdl2::SDLWindow;
+use tuber_graphics_opengl::{opengl, GLSceneRenderer, style::Background};
+
+use tuber::window::Window;
+use tuber::resources::ResourceStore;
+use tuber::scene::{SceneGraph, SceneNode, NodeValue};
+use tuber::graphics::{scene_renderer::SceneRenderer, Sprite};
+use common::{FontStore, GLTextureStore, solid_texture, expect_color};
+
+fn main() -> Result<(), String> {
+    // Setup SDL
+    let sdl_context = sdl2::init()?;
+    let sdl_video_subsystem = sdl_context.video()?;
+    let sdl_event_pump = Rc::new(RefCell::new(sdl_context.event_pump()?));
+
+    // Setup SDL GL context
+    let sdl_gl_attributes = sdl_video_subsystem.gl_attr();
+    sdl_gl_attributes.set_context_profile(sdl2::video::GLProfile::Core);
+    sdl_gl_attributes.set_context_version(3, 3);
+    sdl_gl_attributes.set_depth_size(24);
+
+    // Create window
+    let mut window = SDLWindow::new(&sdl_video_subsystem,
+                                    sdl_event_pump.clone());
+    // Load gl functions
+    opengl::load_symbols(|s| sdl_video_subsystem.gl_get_proc_address(s)
+        as *const std::os::raw::c_void);
+
+    // Shader loading
+    let colored_program = opengl::ShaderProgram::from_files(&[
+        (Path::new("data/colored.vert"), gl::VERTEX_SHADER),
+        (Path::new("data/default.frag"), gl::FRAGMENT_SHADER)
+    ])?;
+    let textured_program = opengl::ShaderProgram::from_files(&[
+        (Path::new("data/textured.vert"), gl::VERTEX_SHADER),
+        (Path::new("data/textured.frag"), gl::FRAGMENT_SHADER)
+    ])?;
+
+    let texture_store = Rc::new(RefCell::new(GLTextureStore::new()));
+    texture_store.borrow_mut().store("red".into(), solid_texture([255, 0, 0, 255]));
+    texture_store.borrow_mut().store("blue".into(), solid_texture([0, 0, 255, 255]));
+    let font_store = Rc::new(RefCell::new(FontStore::new()));
+    let mut scene_renderer = GLSceneRenderer::new(texture_store.clone(), font_store.clone());
+    scene_renderer.set_viewport(0, 0, 800, 600);
+    scene_renderer.set_projection(GLSceneRenderer::screen_ortho(800.0, 600.0));
+
+    colored_program.use_program();
+    check_background_behind_queued_meshes(&mut scene_renderer)?;
+    window.display();
+
+    textured_program.use_program();
+    check_opaque_pass_order(&mut scene_renderer)?;
+    window.display();
+
+    check_depth_test_order(&mut scene_renderer)?;
+    window.display();
+
+    println!("The meshes are drawn in order");
+    Ok(())
+}
+
+/// A rectangle queued before render_scene has the attributes of the
+/// background, but must be drawn over it
+fn check_background_behind_queued_meshes(scene_renderer: &mut GLSceneRenderer)
+    -> Result<(), String> {
+    scene_renderer.set_background(Background::Color((0.0, 0.0, 1.0)));
+    scene_renderer.draw_rect(100.0, 100.0, 200.0, 200.0, (1.0, 0.0, 0.0));
+    scene_renderer.render_scene(&SceneGraph::new());
+    scene_renderer.remove_background();
+
+    expect_color(200.0, 200.0, &[255, 0, 0])?;
+    expect_color(500.0, 400.0, &[0, 0, 255])
+}
+
+/// Submitted parent first: a red square, a smaller blue one over it and an
+/// even smaller red one over both, all at the same depth
+fn nested_squares_scene() -> SceneGraph {
+    let mut first = SceneNode::new("first", NodeValue::SpriteNode(
+            Sprite::new(200.0, 200.0, "red".into())));
+    let mut second = SceneNode::new("second", NodeValue::SpriteNode(
+            Sprite::new(100.0, 100.0, "blue".into())));
+    let third = SceneNode::new("third", NodeValue::SpriteNode(
+            Sprite::new(50.0, 50.0, "red".into())));
+    second.add_child(third);
+    first.add_child(second);
+    let mut scene = SceneGraph::new();
+    scene.root_mut().add_child(first);
+    scene
+}
+
+fn expect_nested_squares() -> Result<(), String> {
+    expect_color(25.0, 25.0, &[255, 0, 0])?;
+    expect_color(75.0, 75.0, &[0, 0, 255])?;
+    expect_color(150.0, 150.0, &[255, 0, 0])
+}
+
+/// Opaque meshes at the same depth are drawn in submission order, even if
+/// meshes with other attributes are submitted between them
+fn check_opaque_pass_order(scene_renderer: &mut GLSceneRenderer) -> Result<(), String> {
+    scene_renderer.set_blend_passes(true);
+    scene_renderer.render_scene(&nested_squares_scene());
+    scene_renderer.set_blend_passes(false);
+    expect_nested_squares()
+}
+
+/// Likewise with the depth test, without blend passes
+fn check_depth_test_order(scene_renderer: &mut GLSceneRenderer) -> Result<(), String> {
+    scene_renderer.enable_depth_test(true);
+    scene_renderer.render_scene(&nested_squares_scene());
+    scene_renderer.enable_depth_test(false);
+    expect_nested_squares()
+}
@@ -0,0 +1,136 @@
+/*
+* MIT License
+*
+* Copyright (c) 2018 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+
+//! Renders a scene into a texture, then draws that texture through a
+//! grayscale shader in a second pass
+
+mod common;
+
+use std::path::Path;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tuber::window::{Window, WindowEvent};
+use tuber::input::keyboard;
+
+use tuber_window_sdl2::SDLWindow;
+use tuber_graphics_opengl::{opengl, GLSceneRenderer, shader::ShaderStore};
+use tuber_graphics_opengl::render_pass::{ClearPolicy, RenderPass, RenderTarget};
+
+use tuber::resources::ResourceStore;
+use tuber::scene::{SceneGraph, SceneNode, NodeValue};
+use tuber::graphics::Sprite;
+use common::{FontStore, GLTextureStore, solid_texture};
+
+fn main() -> Result<(), String> {
+    // Setup SDL
+    let sdl_context = sdl2::init()?;
+    let sdl_video_subsystem = sdl_context.video()?;
+    let sdl_event_pump = Rc::new(RefCell::new(sdl_context.event_pump()?));
+
+    // Setup SDL GL context
+    let sdl_gl_attributes = sdl_video_subsystem.gl_attr();
+    sdl_gl_attributes.set_context_profile(sdl2::video::GLProfile::Core);
+    sdl_gl_attributes.set_context_version(3, 3);
+
+    // Create window
+    let mut window = SDLWindow::new(&sdl_video_subsystem,
+                                    sdl_event_pump.clone());
+    // Load gl functions
+    opengl::load_symbols(|s| sdl_video_subsystem.gl_get_proc_address(s)
+        as *const std::os::raw::c_void);
+
+    // Shader loading
+    let textured_program = load_program("data/textured.frag")?;
+    textured_program.use_program();
+    let shader_store = Rc::new(RefCell::new(ShaderStore::new()));
+    shader_store.borrow_mut().store("grayscale".into(), load_program("data/grayscale.frag")?);
+
+    // Offscreen target
+    let scene_texture = opengl::Texture::from_image(
+        &opengl::DecodedImage::new(800, 600, gl::RGBA, vec![0; 800 * 600 * 4]),
+        &opengl::TextureParameters::new());
+    let framebuffer = opengl::Framebuffer::new();
+    framebuffer.bind();
+    framebuffer.attach_color_texture(&scene_texture, 0);
+    framebuffer.check_complete()?;
+    framebuffer.unbind();
+
+    let texture_store = Rc::new(RefCell::new(GLTextureStore::new()));
+    texture_store.borrow_mut().store("scene_color".into(), scene_texture);
+    texture_store.borrow_mut().store("red".into(), solid_texture([255, 0, 0, 255]));
+    texture_store.borrow_mut().store("blue".into(), solid_texture([0, 0, 255, 255]));
+    let font_store = Rc::new(RefCell::new(FontStore::new()));
+
+    let mut scene = SceneGraph::new();
+    scene.root_mut().add_child(SceneNode::new("red_sprite", NodeValue::SpriteNode(
+            Sprite::new(300.0, 300.0, "red".into()))));
+    scene.root_mut().add_child(SceneNode::new("blue_sprite", NodeValue::SpriteNode(
+            Sprite::new(200.0, 400.0, "blue".into()))));
+    scene.root_mut().add_child(SceneNode::new("post", NodeValue::SpriteNode(
+            Sprite::new(800.0, 600.0, "scene_color".into()))));
+
+    let mut scene_renderer = GLSceneRenderer::new(texture_store.clone(), font_store.clone());
+    scene_renderer.set_shader_store(shader_store.clone());
+    scene_renderer.set_viewport(0, 0, 800, 600);
+
+    let passes = vec!(
+        RenderPass::new(GLSceneRenderer::screen_ortho(800.0, 600.0))
+            .target(RenderTarget::Framebuffer(framebuffer.identifier()))
+            .clear(ClearPolicy::Color((0.2, 0.6, 0.2)))
+            .filter(Box::new(|node: &SceneNode| node.identifier() != "post")),
+        // The texture rows start at the bottom, like the Y axis
+        RenderPass::new(nalgebra_glm::ortho(0.0, 800.0, 0.0, 600.0, 0.0, 100.0))
+            .shader("grayscale")
+            .filter(Box::new(|node: &SceneNode| node.identifier() == "post"))
+    );
+
+    'main_loop: loop {
+        for event in window.poll_event() {
+            match event {
+                WindowEvent::Close |
+                WindowEvent::KeyDown(keyboard::Key::Escape) => break 'main_loop,
+                _ => {}
+            }
+        }
+
+        scene_renderer.render_passes(&scene, &passes);
+        window.display();
+    }
+
+    Ok(())
+}
+
+/// Links data/textured.vert with a fragment shader
+fn load_program(fragment_shader_path: &str) -> Result<opengl::ShaderProgram, String> {
+    let vertex_shader = opengl::Shader::from_file(
+        Path::new("data/textured.vert"),
+        gl::VERTEX_SHADER)?;
+    let fragment_shader = opengl::Shader::from_file(
+        Path::new(fragment_shader_path),
+        gl::FRAGMENT_SHADER)?;
+
+    opengl::ShaderProgram::from_shaders(&[vertex_shader, fragment_shader])
+}
@@ -0,0 +1,115 @@
+/*
+* MIT License
+*
+* Copyright (c) 2018 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+
+//! Renders the same scene side by side with two different cameras
+
+mod common;
+
+use std::path::Path;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tuber::window::{Window, WindowEvent};
+use tuber::input::keyboard;
+
+use tuber_window_sdl2::SDLWindow;
+use tuber_graphics_opengl::{opengl, GLSceneRenderer};
+
+use tuber::scene::{SceneGraph, SceneNode, NodeValue};
+use tuber::graphics::Line;
+use common::{FontStore, GLTextureStore};
+
+fn main() -> Result<(), String> {
+    // Setup SDL
+    let sdl_context = sdl2::init()?;
+    let sdl_video_subsystem = sdl_context.video()?;
+    let sdl_event_pump = Rc::new(RefCell::new(sdl_context.event_pump()?));
+
+    // Setup SDL GL context
+    let sdl_gl_attributes = sdl_video_subsystem.gl_attr();
+    sdl_gl_attributes.set_context_profile(sdl2::video::GLProfile::Core);
+    sdl_gl_attributes.set_context_version(3, 3);
+
+    // Create window
+    let mut window = SDLWindow::new(&sdl_video_subsystem,
+                                    sdl_event_pump.clone());
+    // Load gl functions
+    opengl::load_symbols(|s| sdl_video_subsystem.gl_get_proc_address(s)
+        as *const std::os::raw::c_void);
+
+    // Shader loading
+    let vertex_shader = opengl::Shader::from_file(
+        Path::new("data/colored.vert"),
+        gl::VERTEX_SHADER)?;
+    let fragment_shader = opengl::Shader::from_file(
+        Path::new("data/default.frag"),
+        gl::FRAGMENT_SHADER)?;
+
+    let shader_program = opengl::ShaderProgram::from_shaders(
+        &[vertex_shader, fragment_shader]
+    )?;
+    shader_program.use_program();
+
+    let mut scene = SceneGraph::new();
+    let diagonal = SceneNode::new("diagonal", NodeValue::LineNode(
+            Line::new((0.0, 0.0, 0.0), (400.0, 600.0, 0.0), (1.0, 1.0, 1.0, 1.0))));
+    scene.root_mut().add_child(diagonal);
+    let horizon = SceneNode::new("horizon", NodeValue::LineNode(
+            Line::new((0.0, 300.0, 0.0), (400.0, 300.0, 0.0), (1.0, 1.0, 1.0, 1.0))));
+    scene.root_mut().add_child(horizon);
+
+    let texture_store = Rc::new(RefCell::new(GLTextureStore::new()));
+    let font_store = Rc::new(RefCell::new(FontStore::new()));
+    let mut scene_renderer = GLSceneRenderer::new(texture_store.clone(), font_store.clone());
+    scene_renderer.set_clear_color((0.1, 0.1, 0.2));
+
+    // The left half sees the whole scene, the right half is zoomed in on
+    // its center
+    let full_view = GLSceneRenderer::screen_ortho(400.0, 600.0);
+    let zoomed_view = nalgebra_glm::ortho(100.0, 300.0, 450.0, 150.0, 0.0, 100.0);
+
+    'main_loop: loop {
+        for event in window.poll_event() {
+            match event {
+                WindowEvent::Close |
+                WindowEvent::KeyDown(keyboard::Key::Escape) => break 'main_loop,
+                _ => {}
+            }
+        }
+
+        scene_renderer.set_viewport(0, 0, 800, 600);
+        scene_renderer.clear();
+
+        scene_renderer.draw_rect(150.0, 250.0, 100.0, 100.0, (1.0, 0.5, 0.0));
+        scene_renderer.render_node_in_viewport(scene.root(), (0, 0, 400, 600), &full_view);
+
+        scene_renderer.draw_rect(150.0, 250.0, 100.0, 100.0, (0.0, 0.5, 1.0));
+        scene_renderer.render_node_in_viewport(scene.root(), (400, 0, 400, 600), &zoomed_view);
+
+        window.display();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+/*
+* MIT License
+*
+* Copyright (c) 2018 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+
+//! Renders a sprite, changes its texture between frames and checks that
+//! the new texture is drawn, with and without mesh caching
+
+mod common;
+
+use std::path::Path;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tuber_window_sdl2::SDLWindow;
+use tuber_graphics_opengl::{opengl, GLSceneRenderer};
+
+use tuber::window::Window;
+use tuber::resources::ResourceStore;
+use tuber::scene::{SceneGraph, SceneNode, NodeValue};
+use tuber::graphics::{scene_renderer::SceneRenderer, Sprite};
+use common::{FontStore, GLTextureStore, solid_texture};
+
+fn main() -> Result<(), String> {
+    // Setup SDL
+    let sdl_context = sdl2::init()?;
+    let sdl_video_subsystem = sdl_context.video()?;
+    let sdl_event_pump = Rc::new(RefCell::new(sdl_context.event_pump()?));
+
+    // Setup SDL GL context
+    let sdl_gl_attributes = sdl_video_subsystem.gl_attr();
+    sdl_gl_attributes.set_context_profile(sdl2::video::GLProfile::Core);
+    sdl_gl_attributes.set_context_version(3, 3);
+
+    // Create window
+    let mut window = SDLWindow::new(&sdl_video_subsystem,
+                                    sdl_event_pump.clone());
+    // Load gl functions
+    opengl::load_symbols(|s| sdl_video_subsystem.gl_get_proc_address(s)
+        as *const std::os::raw::c_void);
+
+    // Shader loading
+    let vertex_shader = opengl::Shader::from_file(
+        Path::new("data/textured.vert"),
+        gl::VERTEX_SHADER)?;
+    let fragment_shader = opengl::Shader::from_file(
+        Path::new("data/textured.frag"),
+        gl::FRAGMENT_SHADER)?;
+
+    let shader_program = opengl::ShaderProgram::from_shaders(
+        &[vertex_shader, fragment_shader]
+    )?;
+    shader_program.use_program();
+
+    let texture_store = Rc::new(RefCell::new(GLTextureStore::new()));
+    texture_store.borrow_mut().store("red".into(), solid_texture([255, 0, 0, 255]));
+    texture_store.borrow_mut().store("blue".into(), solid_texture([0, 0, 255, 255]));
+    let font_store = Rc::new(RefCell::new(FontStore::new()));
+
+    let mut scene = SceneGraph::new();
+    let sprite = SceneNode::new("sprite", NodeValue::SpriteNode(
+            Sprite::new(100.0, 100.0, "red".into())));
+    scene.root_mut().add_child(sprite);
+
+    let mut scene_renderer = GLSceneRenderer::new(texture_store.clone(), font_store.clone());
+    scene_renderer.set_viewport(0, 0, 800, 600);
+    scene_renderer.set_projection(GLSceneRenderer::screen_ortho(800.0, 600.0));
+
+    for &mesh_caching in [false, true].iter() {
+        scene_renderer.set_mesh_caching(mesh_caching);
+        scene_renderer.remove_sprite_texture("sprite");
+
+        scene_renderer.render_scene(&scene);
+        expect_sprite_color(&[255, 0, 0])?;
+        window.display();
+
+        scene_renderer.set_sprite_texture("sprite", "blue");
+        scene_renderer.render_scene(&scene);
+        expect_sprite_color(&[0, 0, 255])?;
+        window.display();
+    }
+
+    println!("The sprite is drawn with its new texture");
+    Ok(())
+}
+
+/// Checks the color of the middle of the sprite, in the top-left corner
+fn expect_sprite_color(expected_color: &[u8]) -> Result<(), String> {
+    // Framebuffer rows start at the bottom
+    let pixel = opengl::read_pixels(50, 550, 1, 1);
+    if &pixel[0..3] != expected_color {
+        return Err(format!("Expected the color {:?}, found {:?}",
+                           expected_color, &pixel[0..3]));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+/*
+* MIT License
+*
+* Copyright (c) 2018 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+
+//! Compares writing vertices one at a time through a pointer with copying
+//! the whole slice at once, the two ways of filling a mapped buffer
+
+use std::time::{Duration, Instant};
+
+use tuber_graphics_opengl::Vertex;
+
+const VERTEX_COUNT: usize = 10000;
+const ITERATIONS: u32 = 1000;
+
+fn main() {
+    let vertices: Vec<Vertex> = (0..VERTEX_COUNT)
+        .map(|i| Vertex::with_values((i as f32, 0.0, 0.0), (1.0, 1.0, 1.0), (0.0, 0.0)))
+        .collect();
+    // Stands in for the mapped range of a vertex buffer
+    let mut buffer = vec![Vertex::with_values((0.0, 0.0, 0.0), (0.0, 0.0, 0.0), (0.0, 0.0));
+                          VERTEX_COUNT];
+
+    let per_element = measure(|| unsafe {
+        let mut pointer = buffer.as_mut_ptr();
+        for vertex in vertices.iter() {
+            pointer.write(*vertex);
+            pointer = pointer.offset(1);
+        }
+    });
+    assert_eq!(buffer, vertices);
+
+    buffer.iter_mut().for_each(|vertex| *vertex = vertices[0]);
+    let bulk = measure(|| unsafe {
+        std::slice::from_raw_parts_mut(buffer.as_mut_ptr(), VERTEX_COUNT)
+            .copy_from_slice(&vertices);
+    });
+    assert_eq!(buffer, vertices);
+
+    println!("{} vertices, {} iterations", VERTEX_COUNT, ITERATIONS);
+    println!("Per element copy: {:?} per mesh", per_element / ITERATIONS);
+    println!("Bulk copy: {:?} per mesh", bulk / ITERATIONS);
+}
+
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+
+    start.elapsed()
+}
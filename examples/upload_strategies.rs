@@ -0,0 +1,127 @@
+/*
+* MIT License
+*
+* Copyright (c) 2018 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+
+//! Compares the time taken to render a text-heavy scene with each batch
+//! upload strategy
+
+mod common;
+
+use std::path::Path;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use tuber_window_sdl2::SDLWindow;
+use tuber_graphics_opengl::{opengl, GLSceneRenderer, UploadStrategy, font::*};
+
+use tuber::window::Window;
+use tuber::resources::ResourceStore;
+use tuber::scene::{SceneGraph, SceneNode, NodeValue};
+use tuber::graphics::{scene_renderer::SceneRenderer, Text};
+use common::{FontStore, GLTextureStore};
+
+const TEXT_COUNT: usize = 200;
+const TEXT_LENGTH: usize = 100;
+const FRAME_COUNT: u32 = 100;
+
+fn main() -> Result<(), String> {
+    // Setup SDL
+    let sdl_context = sdl2::init()?;
+    let sdl_video_subsystem = sdl_context.video()?;
+    let sdl_event_pump = Rc::new(RefCell::new(sdl_context.event_pump()?));
+
+    // Setup SDL GL context
+    let sdl_gl_attributes = sdl_video_subsystem.gl_attr();
+    sdl_gl_attributes.set_context_profile(sdl2::video::GLProfile::Core);
+    sdl_gl_attributes.set_context_version(3, 3);
+
+    // Create window
+    let mut window = SDLWindow::new(&sdl_video_subsystem,
+                                    sdl_event_pump.clone());
+    // Load gl functions
+    opengl::load_symbols(|s| sdl_video_subsystem.gl_get_proc_address(s)
+        as *const std::os::raw::c_void);
+
+    // Shader loading
+    let vertex_shader = opengl::Shader::from_file(
+        Path::new("data/textured.vert"),
+        gl::VERTEX_SHADER)?;
+    let fragment_shader = opengl::Shader::from_file(
+        Path::new("data/textured.frag"),
+        gl::FRAGMENT_SHADER)?;
+
+    let shader_program = opengl::ShaderProgram::from_shaders(
+        &[vertex_shader, fragment_shader]
+    )?;
+    shader_program.use_program();
+
+    // A single glyph font is enough to generate many text quads
+    let image = opengl::DecodedImage::new(8, 8, gl::RED, vec![255; 64]);
+    let texture = opengl::Texture::from_image(&image, &opengl::TextureParameters::new());
+    let mut font = Font::new(texture, 8.0, 8.0);
+    font.add_character('a', FontCharacter::new(0.0, 0.0, 8.0, 8.0, 0.0, 0.0, 8.0));
+
+    let font_store = Rc::new(RefCell::new(FontStore::new()));
+    font_store.borrow_mut().store("block_font".into(), font);
+    let texture_store = Rc::new(RefCell::new(GLTextureStore::new()));
+
+    let mut scene = SceneGraph::new();
+    let line: String = std::iter::repeat('a').take(TEXT_LENGTH).collect();
+    for i in 0..TEXT_COUNT {
+        let text = SceneNode::new(&format!("text_{}", i), NodeValue::TextNode(
+                Text::new(line.clone(), "block_font".into())));
+        scene.root_mut().add_child(text);
+    }
+
+    let mut scene_renderer = GLSceneRenderer::new(texture_store.clone(), font_store.clone());
+    scene_renderer.set_viewport(0, 0, 800, 600);
+    scene_renderer.set_projection(GLSceneRenderer::screen_ortho(800.0, 600.0));
+
+    println!("{} texts of {} characters, {} frames", TEXT_COUNT, TEXT_LENGTH, FRAME_COUNT);
+    for &strategy in [UploadStrategy::MapBuffer, UploadStrategy::BufferSubData].iter() {
+        scene_renderer.set_upload_strategy(strategy);
+        scene_renderer.clear_batch_pool();
+        // The first frame creates the batches
+        scene_renderer.render_scene(&scene);
+
+        let elapsed = measure(|| {
+            scene_renderer.render_scene(&scene);
+            unsafe { gl::Finish(); }
+        });
+        window.display();
+        println!("{:?}: {:?} per frame", strategy, elapsed / FRAME_COUNT);
+    }
+
+    Ok(())
+}
+
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..FRAME_COUNT {
+        f();
+    }
+
+    start.elapsed()
+}
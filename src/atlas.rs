@@ -0,0 +1,200 @@
+/*
+* MIT License
+*
+* Copyright (c) 2019 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+
+//! This module contains the packing of several images into one texture
+
+use std::collections::HashMap;
+
+use crate::opengl;
+use crate::style::TextureRegion;
+
+/// Largest width and height of a generated atlas
+const MAX_ATLAS_SIZE: i32 = 8192;
+
+/// Places rectangles in rows from the top-left corner of a square
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::atlas::ShelfPacker;
+///
+/// let mut packer = ShelfPacker::new(16);
+/// assert_eq!(packer.allocate(8, 4), Some((0, 0)));
+/// assert_eq!(packer.allocate(7, 2), Some((9, 0)));
+/// assert_eq!(packer.allocate(8, 8), Some((0, 5)));
+/// assert_eq!(packer.allocate(16, 16), None);
+/// ```
+pub struct ShelfPacker {
+    size: i32,
+    cursor: (i32, i32),
+    row_height: i32
+}
+
+impl ShelfPacker {
+    /// Space left between two rectangles so that they don't bleed into
+    /// each other
+    const PADDING: i32 = 1;
+
+    pub fn new(size: i32) -> ShelfPacker {
+        ShelfPacker {
+            size,
+            cursor: (0, 0),
+            row_height: 0
+        }
+    }
+
+    /// Returns the top-left corner of a free rectangle of the given size,
+    /// None if there isn't enough space left
+    pub fn allocate(&mut self, width: i32, height: i32) -> Option<(i32, i32)> {
+        if self.cursor.0 + width > self.size {
+            self.cursor = (0, self.cursor.1 + self.row_height + ShelfPacker::PADDING);
+            self.row_height = 0;
+        }
+
+        if width > self.size || self.cursor.1 + height > self.size {
+            return None;
+        }
+
+        let position = self.cursor;
+        self.cursor.0 += width + ShelfPacker::PADDING;
+        self.row_height = self.row_height.max(height);
+        Some(position)
+    }
+}
+
+/// Computes the size of a square atlas fitting rectangles of the given
+/// sizes and their positions in it
+///
+/// The size is the smallest power of two that fits, the rectangles are
+/// placed from the tallest one. Returns None if they don't fit in the
+/// largest atlas.
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::atlas;
+///
+/// let (size, positions) = atlas::layout(&[(16, 16), (16, 32), (8, 8)]).unwrap();
+/// assert_eq!(size, 64);
+/// assert_eq!(positions, vec!((17, 0), (0, 0), (34, 0)));
+/// ```
+pub fn layout(sizes: &[(i32, i32)]) -> Option<(i32, Vec<(i32, i32)>)> {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by(|&a, &b| sizes[b].1.cmp(&sizes[a].1));
+
+    let mut size = 1;
+    while size <= MAX_ATLAS_SIZE {
+        let mut packer = ShelfPacker::new(size);
+        let mut positions = vec![(0, 0); sizes.len()];
+        let fits = order.iter().all(|&index| {
+            let (width, height) = sizes[index];
+            match packer.allocate(width, height) {
+                Some(position) => {
+                    positions[index] = position;
+                    true
+                },
+                None => false
+            }
+        });
+
+        if fits {
+            return Some((size, positions));
+        }
+
+        size *= 2;
+    }
+
+    None
+}
+
+/// Packs images into one RGBA texture
+///
+/// Each image is decoded by the given function, which can be the decoding
+/// step of a texture loader. The texture regions are returned with the
+/// path of their image and can be used as rectangle style regions. The
+/// images are expected to have their rows tightly packed. Images without
+/// pixels get an empty region.
+pub fn pack<F>(paths: &[&str], mut decode: F,
+               parameters: &opengl::TextureParameters)
+    -> Result<(opengl::Texture, HashMap<String, TextureRegion>), String>
+where
+    F: FnMut(&str) -> Result<opengl::DecodedImage, String> {
+    let mut images = vec!();
+    for path in paths {
+        images.push((path.to_string(), decode(path)?));
+    }
+
+    pack_images(&images, parameters)
+}
+
+/// Packs decoded images into one RGBA texture, see `pack`
+pub fn pack_images(images: &[(String, opengl::DecodedImage)],
+                   parameters: &opengl::TextureParameters)
+    -> Result<(opengl::Texture, HashMap<String, TextureRegion>), String> {
+    let sizes: Vec<(i32, i32)> = images.iter()
+        .map(|(_, image)| (image.width(), image.height()))
+        .collect();
+    let (size, positions) = layout(&sizes)
+        .ok_or("The images don't fit in an atlas")?;
+
+    let mut pixels = vec![0u8; (size * size * 4) as usize];
+    let mut regions = HashMap::new();
+    for ((name, image), &(x, y)) in images.iter().zip(positions.iter()) {
+        let channels = match image.format() {
+            gl::RGBA => 4,
+            gl::RGB => 3,
+            gl::RED => 1,
+            _ => return Err(format!("Unsupported image format for {}", name))
+        };
+
+        // Empty images have no pixels to copy, only an empty region
+        let width = image.width() as usize;
+        if width > 0 {
+            for (row_index, row) in image.pixels().chunks(width * channels)
+                .take(image.height() as usize)
+                .enumerate() {
+                for (column, pixel) in row.chunks(channels).enumerate() {
+                    let rgba = match channels {
+                        4 => [pixel[0], pixel[1], pixel[2], pixel[3]],
+                        3 => [pixel[0], pixel[1], pixel[2], 255],
+                        _ => [pixel[0], pixel[0], pixel[0], 255]
+                    };
+                    let offset = (((y as usize + row_index) * size as usize)
+                        + x as usize + column) * 4;
+                    pixels[offset..offset + 4].copy_from_slice(&rgba);
+                }
+            }
+        }
+
+        let atlas_size = size as f32;
+        regions.insert(name.clone(), (x as f32 / atlas_size,
+                                      y as f32 / atlas_size,
+                                      image.width() as f32 / atlas_size,
+                                      image.height() as f32 / atlas_size));
+    }
+
+    let atlas_image = opengl::DecodedImage::new(size, size, gl::RGBA, pixels);
+    Ok((opengl::Texture::from_image(&atlas_image, parameters), regions))
+}
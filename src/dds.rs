@@ -0,0 +1,178 @@
+/*
+* MIT License
+*
+* Copyright (c) 2019 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+
+//! This module contains the decoding of DDS files holding S3TC compressed
+//! images
+
+use crate::opengl;
+
+const DDS_MAGIC: &[u8] = b"DDS ";
+const HEADER_SIZE: usize = 128;
+const HEIGHT_OFFSET: usize = 12;
+const WIDTH_OFFSET: usize = 16;
+const MIPMAP_COUNT_OFFSET: usize = 28;
+const FOURCC_OFFSET: usize = 84;
+
+/// Decodes a DDS file compressed with DXT1, DXT3 or DXT5
+///
+/// The rows are flipped so that the first one is the bottom one, like the
+/// images uploaded by the other loaders. This is exact for heights that
+/// are multiples of 4 or smaller than 4, which covers power-of-two
+/// textures. An error is returned if the file declares more mipmaps than
+/// a complete chain of its size.
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::{dds, opengl};
+///
+/// let mut file = vec![0; 128];
+/// file[0..4].copy_from_slice(b"DDS ");
+/// file[12] = 4; // Height
+/// file[16] = 8; // Width
+/// file[28] = 1; // Mipmap count
+/// file[84..88].copy_from_slice(b"DXT1");
+/// file.extend_from_slice(&[0; 16]);
+///
+/// let image = dds::decode(&file).unwrap();
+/// assert_eq!((image.width(), image.height()), (8, 4));
+/// assert_eq!(image.internal_format(), opengl::COMPRESSED_RGBA_S3TC_DXT1);
+/// assert_eq!(image.levels().len(), 1);
+///
+/// assert!(dds::decode(b"PNG").is_err());
+///
+/// // A 1x1 image can't have 64 levels, whatever the size of the file
+/// let mut file = vec![0; 128];
+/// file[0..4].copy_from_slice(b"DDS ");
+/// file[12] = 1; // Height
+/// file[16] = 1; // Width
+/// file[28] = 64; // Mipmap count
+/// file[84..88].copy_from_slice(b"DXT1");
+/// file.extend_from_slice(&[0; 8 * 64]);
+///
+/// assert!(dds::decode(&file).is_err());
+/// ```
+pub fn decode(bytes: &[u8]) -> Result<opengl::CompressedImage, String> {
+    if bytes.len() < HEADER_SIZE || &bytes[0..4] != DDS_MAGIC {
+        return Err("Not a DDS file".into());
+    }
+
+    let read_u32 = |offset: usize| {
+        let mut value = [0; 4];
+        value.copy_from_slice(&bytes[offset..offset + 4]);
+        u32::from_le_bytes(value)
+    };
+
+    let height = read_u32(HEIGHT_OFFSET) as usize;
+    let width = read_u32(WIDTH_OFFSET) as usize;
+    let level_count = (read_u32(MIPMAP_COUNT_OFFSET) as usize).max(1);
+    let (internal_format, block_size) = match &bytes[FOURCC_OFFSET..FOURCC_OFFSET + 4] {
+        b"DXT1" => (opengl::COMPRESSED_RGBA_S3TC_DXT1, 8),
+        b"DXT3" => (opengl::COMPRESSED_RGBA_S3TC_DXT3, 16),
+        b"DXT5" => (opengl::COMPRESSED_RGBA_S3TC_DXT5, 16),
+        _ => return Err("Unsupported DDS pixel format".into())
+    };
+    if level_count > opengl::mipmap_level_count(width, height) {
+        return Err(format!("Too many mipmaps for a {}x{} DDS file", width, height));
+    }
+
+    let mut levels = vec!();
+    let mut offset = HEADER_SIZE;
+    for level in 0..level_count {
+        let level_width = (width >> level).max(1);
+        let level_height = (height >> level).max(1);
+        let blocks_per_row = (level_width + 3) / 4;
+        let block_rows = (level_height + 3) / 4;
+        let level_size = blocks_per_row * block_rows * block_size;
+        if offset + level_size > bytes.len() {
+            return Err("Truncated DDS file".into());
+        }
+
+        let row_size = blocks_per_row * block_size;
+        let mut level_data = Vec::with_capacity(level_size);
+        for block_row in (0..block_rows).rev() {
+            let row_start = offset + block_row * row_size;
+            let mut row = bytes[row_start..row_start + row_size].to_vec();
+            for block in row.chunks_mut(block_size) {
+                flip_block(block, internal_format, level_height.min(4));
+            }
+            level_data.extend_from_slice(&row);
+        }
+
+        levels.push(level_data);
+        offset += level_size;
+    }
+
+    Ok(opengl::CompressedImage::new(width as gl::types::GLsizei,
+                                    height as gl::types::GLsizei,
+                                    internal_format,
+                                    levels))
+}
+
+/// Reads a DDS file and uploads it to a new texture
+///
+/// The mipmaps stored in the file are uploaded as well. An error is
+/// returned if the context doesn't support S3TC compression.
+pub fn load(path: &std::path::Path,
+            parameters: &opengl::TextureParameters) -> Result<opengl::Texture, String> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    let image = decode(&bytes)?;
+    opengl::Texture::from_compressed_image(&image, parameters)
+}
+
+/// Reverses the order of the first rows of pixels of a block
+fn flip_block(block: &mut [u8], internal_format: gl::types::GLenum, rows: usize) {
+    // The color part comes after the alpha part for DXT3 and DXT5, its
+    // indices are stored one row per byte after the two colors
+    let color_offset = block.len() - 8;
+    block[color_offset + 4..color_offset + 4 + rows].reverse();
+
+    match internal_format {
+        opengl::COMPRESSED_RGBA_S3TC_DXT3 => {
+            // 4 bits per alpha value, two bytes per row
+            for row in 0..rows / 2 {
+                let mirrored_row = rows - 1 - row;
+                block.swap(row * 2, mirrored_row * 2);
+                block.swap(row * 2 + 1, mirrored_row * 2 + 1);
+            }
+        },
+        opengl::COMPRESSED_RGBA_S3TC_DXT5 => {
+            // 3 bits per alpha index after the two alpha values, 12 bits
+            // per row
+            let indices = block[2..8].iter().enumerate()
+                .fold(0u64, |indices, (i, &byte)| indices | (byte as u64) << (8 * i));
+            let row_indices = |row: usize| (indices >> (12 * row)) & 0xFFF;
+            let flipped_indices = (0..4).fold(0u64, |flipped_indices, row| {
+                let source_row = if row < rows { rows - 1 - row } else { row };
+                flipped_indices | row_indices(source_row) << (12 * row)
+            });
+            for (i, byte) in block[2..8].iter_mut().enumerate() {
+                *byte = (flipped_indices >> (8 * i)) as u8;
+            }
+        },
+        _ => {}
+    }
+}
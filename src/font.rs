@@ -23,13 +23,19 @@
 */
 
 use crate::opengl;
+#[cfg(feature = "truetype")]
+use crate::atlas::ShelfPacker;
 use std::collections::HashMap;
 
 pub struct Font {
     characters: HashMap<char, FontCharacter>,
     texture: opengl::Texture,
     horizontal_scale: f32,
-    vertical_scale: f32
+    vertical_scale: f32,
+    line_height: f32,
+    base: f32,
+    #[cfg(feature = "truetype")]
+    rasterizer: Option<GlyphRasterizer>
 }
 
 impl Font {
@@ -40,10 +46,152 @@ impl Font {
             characters: HashMap::new(),
             texture,
             horizontal_scale,
-            vertical_scale
+            vertical_scale,
+            line_height: 0.0,
+            base: 0.0,
+            #[cfg(feature = "truetype")]
+            rasterizer: None
         }
     }
 
+    /// Loads a TrueType font and rasterizes its glyphs at the given size in
+    /// pixels
+    ///
+    /// The printable ASCII characters are rasterized right away, other
+    /// characters are added to the atlas by `add_glyphs`.
+    #[cfg(feature = "truetype")]
+    pub fn from_truetype(path: &std::path::Path, size: f32) -> Result<Font, String> {
+        let data = std::fs::read(path).map_err(|error| error.to_string())?;
+        let truetype_font = rusttype::Font::from_bytes(data)
+            .map_err(|error| error.to_string())?;
+        let scale = rusttype::Scale::uniform(size);
+        let v_metrics = truetype_font.v_metrics(scale);
+
+        let image = opengl::DecodedImage::new(TRUETYPE_ATLAS_SIZE,
+                                              TRUETYPE_ATLAS_SIZE,
+                                              gl::RED,
+                                              vec![0; (TRUETYPE_ATLAS_SIZE * TRUETYPE_ATLAS_SIZE) as usize]);
+        let texture = opengl::Texture::from_image(&image, &opengl::TextureParameters::new());
+        texture.set_swizzle([gl::ONE, gl::ONE, gl::ONE, gl::RED]);
+
+        let mut font = Font::new(texture,
+                                 TRUETYPE_ATLAS_SIZE as f32,
+                                 TRUETYPE_ATLAS_SIZE as f32);
+        font.set_metrics(v_metrics.ascent - v_metrics.descent + v_metrics.line_gap,
+                         v_metrics.ascent);
+        font.rasterizer = Some(GlyphRasterizer {
+            font: truetype_font,
+            scale,
+            ascent: v_metrics.ascent,
+            packer: ShelfPacker::new(TRUETYPE_ATLAS_SIZE)
+        });
+
+        let ascii: String = (0x20u8..0x7f).map(|c| c as char).collect();
+        font.add_glyphs(&ascii);
+        Ok(font)
+    }
+
+    /// Adds the missing characters of a text to the atlas of a font loaded
+    /// from a TrueType file
+    ///
+    /// This does nothing for other fonts.
+    #[cfg(feature = "truetype")]
+    pub fn add_glyphs(&mut self, text: &str) {
+        for character in text.chars() {
+            if !self.characters.contains_key(&character) {
+                self.rasterize(character);
+            }
+        }
+    }
+
+    /// Adds the missing characters of a text to the atlas of a font loaded
+    /// from a TrueType file
+    ///
+    /// This does nothing for other fonts.
+    #[cfg(not(feature = "truetype"))]
+    pub fn add_glyphs(&mut self, _text: &str) {}
+
+    /// Returns whether `add_glyphs` would rasterize some characters of a
+    /// text
+    #[cfg(feature = "truetype")]
+    pub fn lacks_glyphs(&self, text: &str) -> bool {
+        self.rasterizer.is_some()
+            && text.chars().any(|character| !self.characters.contains_key(&character))
+    }
+
+    /// Returns whether `add_glyphs` would rasterize some characters of a
+    /// text
+    #[cfg(not(feature = "truetype"))]
+    pub fn lacks_glyphs(&self, _text: &str) -> bool {
+        false
+    }
+
+    /// Rasterizes a glyph into the atlas texture
+    ///
+    /// The atlas rows are stored bottom to top like the images of bitmap
+    /// fonts, glyphs that don't fit anymore are left out.
+    #[cfg(feature = "truetype")]
+    fn rasterize(&mut self, character: char) {
+        let rasterizer = match self.rasterizer.as_mut() {
+            Some(rasterizer) => rasterizer,
+            None => return
+        };
+
+        let glyph = rasterizer.font.glyph(character).scaled(rasterizer.scale);
+        let x_advance = glyph.h_metrics().advance_width;
+        let glyph = glyph.positioned(rusttype::point(0.0, rasterizer.ascent));
+        let bounds = match glyph.pixel_bounding_box() {
+            Some(bounds) => bounds,
+            None => {
+                // Blank glyphs such as spaces only advance the cursor
+                self.characters.insert(character,
+                    FontCharacter::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, x_advance));
+                return;
+            }
+        };
+
+        let (width, height) = (bounds.width(), bounds.height());
+        let (x, y) = match rasterizer.packer.allocate(width, height) {
+            Some(position) => position,
+            None => return
+        };
+
+        let mut pixels = vec![0u8; (width * height) as usize];
+        glyph.draw(|glyph_x, glyph_y, coverage| {
+            let row = height as u32 - 1 - glyph_y;
+            pixels[(row * width as u32 + glyph_x) as usize] = (coverage * 255.0).round() as u8;
+        });
+
+        self.texture.bind();
+        opengl::set_unpack_alignment(1);
+        self.texture.set_2d_sub_image_data(0,
+                                           x,
+                                           TRUETYPE_ATLAS_SIZE - y - height,
+                                           width,
+                                           height,
+                                           gl::RED,
+                                           gl::UNSIGNED_BYTE,
+                                           pixels.as_ptr() as *const gl::types::GLvoid);
+        opengl::set_unpack_alignment(4);
+        self.texture.unbind();
+
+        self.characters.insert(character, FontCharacter::new(
+            x as f32,
+            y as f32,
+            width as f32,
+            height as f32,
+            bounds.min.x as f32,
+            bounds.min.y as f32,
+            x_advance));
+    }
+
+    /// Sets the distance between two lines of text and the distance from
+    /// the top of a line to its baseline
+    pub fn set_metrics(&mut self, line_height: f32, base: f32) {
+        self.line_height = line_height;
+        self.base = base;
+    }
+
     pub fn add_character(&mut self, character: char,
                          metadata: FontCharacter) {
         self.characters.insert(character, metadata);
@@ -60,6 +208,21 @@ impl Font {
         self.vertical_scale
     }
 
+    /// Returns the distance between two lines of text, 0 if unknown
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    /// Returns the distance from the top of a line to its baseline, 0 if
+    /// unknown
+    pub fn base(&self) -> f32 {
+        self.base
+    }
+
+    pub fn texture(&self) -> &opengl::Texture {
+        &self.texture
+    }
+
     pub fn bind_texture(&self) {
         self.texture.bind();
     }
@@ -118,4 +281,17 @@ impl FontCharacter {
     pub fn x_advance(&self) -> f32 {
         self.x_advance
     }
-}
\ No newline at end of file
+}
+
+/// Width and height of the atlas textures of TrueType fonts
+#[cfg(feature = "truetype")]
+const TRUETYPE_ATLAS_SIZE: i32 = 1024;
+
+/// TrueType font rasterizing glyphs into an atlas
+#[cfg(feature = "truetype")]
+struct GlyphRasterizer {
+    font: rusttype::Font<'static>,
+    scale: rusttype::Scale,
+    ascent: f32,
+    packer: ShelfPacker
+}
@@ -21,161 +21,1507 @@
 * SOFTWARE.
 */
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 use tuber::graphics::scene_renderer::SceneRenderer;
 use tuber::resources::ResourceStore;
 
 use tuber::scene::{SceneGraph, SceneNode, NodeValue};
 
+use crate::render_pass::{ClearPolicy, RenderPass};
+
 pub mod opengl;
 pub mod font;
+pub mod shader;
+pub mod style;
+pub mod atlas;
+pub mod dds;
+pub mod render_pass;
 
 type RGBColor = (f32, f32, f32);
 type VertexIndex = gl::types::GLuint;
+pub type NodeIdentifier = String;
+/// Rectangle of the viewport given as (x, y, width, height) in logical
+/// units from its top-left corner
+pub type ClipRect = (i32, i32, i32, i32);
+/// Minification and magnification filters of a texture
+pub type TextureFilter = (gl::types::GLenum, gl::types::GLenum);
+
+/// Name of the shader uniform the projection is uploaded to
+const PROJECTION_UNIFORM: &str = "transform";
+
+/// Colors tinting the batches when debugging them
+const DEBUG_BATCH_PALETTE: [RGBColor; 6] = [
+    (1.0, 0.3, 0.3),
+    (0.3, 1.0, 0.3),
+    (0.3, 0.3, 1.0),
+    (1.0, 1.0, 0.3),
+    (1.0, 0.3, 1.0),
+    (0.3, 1.0, 1.0)
+];
 
 pub struct GLSceneRenderer {
     pending_meshes: Vec<Mesh>,
     pending_batches: Vec<RenderBatch>,
-    texture_store: Rc<RefCell<ResourceStore<opengl::Texture>>>,
-    font_store: Rc<RefCell<ResourceStore<font::Font>>>
+    batch_pool: Vec<RenderBatch>,
+    render_count: u64,
+    drawn_batch_count: usize,
+    texture_store: Rc<RefCell<dyn ResourceStore<opengl::Texture>>>,
+    font_store: Rc<RefCell<dyn ResourceStore<font::Font>>>,
+    shader_store: Option<Rc<RefCell<dyn ResourceStore<opengl::ShaderProgram>>>>,
+    node_shaders: HashMap<NodeIdentifier, String>,
+    clear_color: RGBColor,
+    auto_clear: bool,
+    viewport: (i32, i32, i32, i32),
+    projection: nalgebra_glm::Mat4,
+    upload_projection: bool,
+    pick_regions: Vec<PickRegion>,
+    retained_mode: bool,
+    dirty: bool,
+    frame_changed: bool,
+    previous_meshes: Vec<Mesh>,
+    previous_background: Option<Mesh>,
+    text_styles: HashMap<NodeIdentifier, style::TextStyle>,
+    rectangle_styles: HashMap<NodeIdentifier, style::RectangleStyle>,
+    line_styles: HashMap<NodeIdentifier, style::LineStyle>,
+    sprite_transforms: HashMap<NodeIdentifier, style::SpriteTransform>,
+    node_transforms: HashMap<NodeIdentifier, style::NodeTransform>,
+    node_instancing: bool,
+    background: Option<style::Background>,
+    sprite_texture_unit: TextureUnit,
+    font_texture_unit: TextureUnit,
+    debug_batches: bool,
+    verbose: bool,
+    upload_strategy: UploadStrategy,
+    node_clips: HashMap<NodeIdentifier, ClipRect>,
+    mesh_caching: bool,
+    mesh_cache: HashMap<NodeIdentifier, Vec<(Vec<NodeIdentifier>, Vec<Mesh>)>>,
+    vsync: bool,
+    swap_interval_callback: Option<Box<dyn FnMut(i32)>>,
+    last_frame_start: Option<Instant>,
+    frame_delta: Option<Duration>,
+    default_texture_filter: Rc<Cell<TextureFilter>>,
+    state_isolation: bool,
+    sprite_palettes: HashMap<NodeIdentifier, String>,
+    palette_texture_unit: TextureUnit,
+    framebuffer_scale: f32,
+    missing_texture: Option<opengl::Texture>,
+    reported_missing_resources: HashSet<String>,
+    node_blend_modes: HashMap<NodeIdentifier, opengl::BlendMode>,
+    blend_passes: bool,
+    depth_test: bool,
+    sprite_textures: HashMap<NodeIdentifier, String>,
+    sprite_regions: HashMap<NodeIdentifier, style::TextureRegion>
 }
 impl GLSceneRenderer {
+    /// Maximum number of batches kept for the next frames
+    pub const BATCH_POOL_CAPACITY: usize = 64;
+    /// Number of calls to `render` after which a batch that hasn't been
+    /// reused is destroyed
+    pub const BATCH_POOL_MAX_IDLE_RENDERS: u64 = 120;
+
     /// Creates a new OpenGL scene renderer
-    pub fn new(texture_store: Rc<RefCell<ResourceStore<opengl::Texture>>>,
-               font_store: Rc<RefCell<ResourceStore<font::Font>>>) -> GLSceneRenderer {
+    pub fn new(texture_store: Rc<RefCell<dyn ResourceStore<opengl::Texture>>>,
+               font_store: Rc<RefCell<dyn ResourceStore<font::Font>>>) -> GLSceneRenderer {
         GLSceneRenderer {
             pending_meshes: vec!(),
             pending_batches: vec!(),
+            batch_pool: vec!(),
+            render_count: 0,
+            drawn_batch_count: 0,
             texture_store,
-            font_store
+            font_store,
+            shader_store: None,
+            node_shaders: HashMap::new(),
+            clear_color: (0.0, 0.0, 0.0),
+            auto_clear: true,
+            viewport: (0, 0, 0, 0),
+            projection: nalgebra_glm::identity(),
+            upload_projection: false,
+            pick_regions: vec!(),
+            retained_mode: false,
+            dirty: true,
+            frame_changed: true,
+            previous_meshes: vec!(),
+            previous_background: None,
+            text_styles: HashMap::new(),
+            rectangle_styles: HashMap::new(),
+            line_styles: HashMap::new(),
+            sprite_transforms: HashMap::new(),
+            node_transforms: HashMap::new(),
+            node_instancing: false,
+            background: None,
+            sprite_texture_unit: TextureUnit::new(0),
+            font_texture_unit: TextureUnit::new(0),
+            debug_batches: false,
+            verbose: false,
+            upload_strategy: UploadStrategy::MapBuffer,
+            node_clips: HashMap::new(),
+            mesh_caching: false,
+            mesh_cache: HashMap::new(),
+            vsync: true,
+            swap_interval_callback: None,
+            last_frame_start: None,
+            frame_delta: None,
+            default_texture_filter: Rc::new(Cell::new((gl::NEAREST, gl::NEAREST))),
+            state_isolation: false,
+            sprite_palettes: HashMap::new(),
+            palette_texture_unit: TextureUnit::new(1).sampler("palette"),
+            framebuffer_scale: 1.0,
+            missing_texture: None,
+            reported_missing_resources: HashSet::new(),
+            node_blend_modes: HashMap::new(),
+            blend_passes: false,
+            depth_test: false,
+            sprite_textures: HashMap::new(),
+            sprite_regions: HashMap::new()
         }
     }
 
+    /// Sets the color used to clear the frame
     pub fn set_clear_color(&mut self, color: RGBColor) {
+        self.clear_color = color;
         opengl::set_clear_color(color.0, color.1, color.2);
     }
 
+    /// Sets whether the frame is cleared at the start of render_scene
+    ///
+    /// Auto-clear is enabled by default. Disable it to composite several
+    /// scenes in the same frame and call `clear` manually instead.
+    pub fn set_auto_clear(&mut self, auto_clear: bool) {
+        self.auto_clear = auto_clear;
+    }
+
+    /// Clears the frame with the clear color
+    ///
+    /// The nodes rendered since the previous clear can't be picked anymore
+    pub fn clear(&mut self) {
+        self.clear_frame();
+        self.pick_regions.clear();
+    }
+
+    fn clear_frame(&mut self) {
+        let c = self.clear_color;
+        opengl::set_clear_color(c.0, c.1, c.2);
+        if self.blend_passes || self.depth_test {
+            opengl::clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        } else {
+            opengl::clear(gl::COLOR_BUFFER_BIT);
+        }
+    }
+
+    /// Sets the background drawn behind the scene by render_scene
+    ///
+    /// The background covers the whole viewport whatever the projection,
+    /// it is drawn over the clear color.
+    pub fn set_background(&mut self, background: style::Background) {
+        self.background = Some(background);
+    }
+
+    /// Removes the background, only the clear color remains behind the
+    /// scene
+    pub fn remove_background(&mut self) {
+        self.background = None;
+    }
+
+    /// Generates the mesh of the background
+    ///
+    /// Its vertices are the corners of the viewport brought back to world
+    /// coordinates with the projection.
+    fn background_mesh(&self) -> Option<Mesh> {
+        const RADIAL_GRADIENT_SEGMENTS: usize = 32;
+
+        let background = self.background?;
+
+        let inverse_projection = nalgebra_glm::inverse(&self.projection);
+        let vertex = |ndc_x: f32, ndc_y: f32, color: RGBColor| {
+            let world = inverse_projection * nalgebra_glm::vec4(ndc_x, ndc_y, 0.0, 1.0);
+            Vertex::with_values((world[0] / world[3], world[1] / world[3], world[2] / world[3]),
+                                color,
+                                (0.0, 0.0))
+        };
+
+        let mut mesh = Mesh::new(MeshAttributes::defaults());
+        match background {
+            style::Background::Color(color) => {
+                mesh.add_quad(&[vertex(-1.0, 1.0, color), vertex(-1.0, -1.0, color),
+                                vertex(1.0, -1.0, color), vertex(1.0, 1.0, color)]);
+            },
+            style::Background::VerticalGradient { top, bottom } => {
+                mesh.add_quad(&[vertex(-1.0, 1.0, top), vertex(-1.0, -1.0, bottom),
+                                vertex(1.0, -1.0, bottom), vertex(1.0, 1.0, top)]);
+            },
+            style::Background::RadialGradient { center, edge } => {
+                // The ring goes through the corners of the viewport
+                let radius = std::f32::consts::SQRT_2;
+                mesh.add_vertices(&[vertex(0.0, 0.0, center)]);
+                for i in 0..RADIAL_GRADIENT_SEGMENTS {
+                    let angle = 2.0 * std::f32::consts::PI
+                        * i as f32 / RADIAL_GRADIENT_SEGMENTS as f32;
+                    mesh.add_vertices(&[vertex(radius * angle.cos(),
+                                               radius * angle.sin(),
+                                               edge)]);
+                }
+
+                for i in 0..RADIAL_GRADIENT_SEGMENTS {
+                    let next = (i + 1) % RADIAL_GRADIENT_SEGMENTS;
+                    mesh.add_indices(&[0, 1 + i as VertexIndex, 1 + next as VertexIndex]);
+                }
+            }
+        }
+
+        Some(mesh)
+    }
+
+    /// Draws the background in a batch of its own
+    ///
+    /// Meshes queued before render_scene, like the ones of `draw_rect`,
+    /// have the attributes of the background. Batched with it, they would
+    /// be drawn before it and hidden.
+    fn render_background(&mut self, background: Mesh) {
+        let queued_meshes = std::mem::replace(&mut self.pending_meshes, vec!(background));
+        self.render();
+        self.pending_meshes = queued_meshes;
+    }
+
+    /// Sets whether unchanged scenes are redrawn
+    ///
+    /// In retained mode, render_scene does nothing if the scene produces
+    /// the same meshes as the previous frame. The previous frame is then
+    /// still in the framebuffer, so the window must only be displayed
+    /// when `needs_redraw` returns true.
+    pub fn set_retained_mode(&mut self, retained_mode: bool) {
+        self.retained_mode = retained_mode;
+        self.previous_meshes.clear();
+        self.previous_background = None;
+        self.dirty = true;
+    }
+
+    /// Returns whether the window needs to be redrawn
+    ///
+    /// This is the case if the last call to render_scene rendered a new
+    /// frame, or if `mark_dirty` has been called since. Always returns
+    /// true outside of retained mode.
+    pub fn needs_redraw(&self) -> bool {
+        !self.retained_mode || self.dirty || self.frame_changed
+    }
+
+    /// Forces the next call to render_scene to render the scene
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Sets whether render_scene restores the OpenGL state it modifies
+    ///
+    /// With state isolation enabled, the bound program, vertex array
+    /// object, array buffer, textures of the sprite, font and palette
+    /// units, active texture unit, blending, front face and scissor test
+    /// are saved before rendering the scene and restored afterwards. This
+    /// is meant for renderers embedded in a larger OpenGL application, at
+    /// the cost of a few state queries per frame.
+    pub fn set_state_isolation(&mut self, state_isolation: bool) {
+        self.state_isolation = state_isolation;
+    }
+
+    /// Sets whether the frames are expected to be synchronized with the
+    /// display
+    ///
+    /// Vsync is expected by default. The buffers are swapped by the window,
+    /// so this only has an effect through the swap interval callback.
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.vsync = vsync;
+        self.notify_swap_interval();
+    }
+
+    /// Returns whether the frames are expected to be synchronized with the
+    /// display
+    pub fn expects_vsync(&self) -> bool {
+        self.vsync
+    }
+
+    /// Sets the function applying the swap interval expected by the renderer
+    ///
+    /// The callback is called right away, then every time `set_vsync`
+    /// changes the expectation, with 1 if vsync is expected and 0
+    /// otherwise. With SDL, it would typically call
+    /// `VideoSubsystem::gl_set_swap_interval`.
+    pub fn set_swap_interval_callback(&mut self, callback: Box<dyn FnMut(i32)>) {
+        self.swap_interval_callback = Some(callback);
+        self.notify_swap_interval();
+    }
+
+    fn notify_swap_interval(&mut self) {
+        let swap_interval = if self.vsync { 1 } else { 0 };
+        if let Some(callback) = self.swap_interval_callback.as_mut() {
+            callback(swap_interval);
+        }
+    }
+
+    /// Returns the time elapsed between the starts of the last two calls to
+    /// render_scene, or None before the second one
+    pub fn frame_delta(&self) -> Option<Duration> {
+        self.frame_delta
+    }
+
+    fn measure_frame(&mut self) {
+        let now = Instant::now();
+        self.frame_delta = self.last_frame_start.map(|start| now - start);
+        self.last_frame_start = Some(now);
+    }
+
+    /// Sets the filters the texture loaders use for the textures loaded
+    /// without parameters of their own
+    ///
+    /// The renderer doesn't load textures itself, the loaders read the
+    /// filters through the handle returned by `default_texture_filter`.
+    /// Textures already loaded keep their filters.
+    pub fn set_default_texture_filter(&mut self, min_filter: gl::types::GLenum,
+                                      mag_filter: gl::types::GLenum) {
+        self.default_texture_filter.set((min_filter, mag_filter));
+    }
+
+    /// Returns a handle to the default texture filters, to be shared with
+    /// the texture loaders
+    pub fn default_texture_filter(&self) -> Rc<Cell<TextureFilter>> {
+        self.default_texture_filter.clone()
+    }
+
+    /// Sets the viewport in logical units, from the bottom-left corner of
+    /// the window
+    ///
+    /// The viewport is converted to pixels with the framebuffer scale.
     pub fn set_viewport(&mut self, x: i32, y: i32,
                         width: i32, height: i32) {
+        self.viewport = (x, y, width, height);
+        let (x, y, width, height) = scale_rectangle((x, y, width, height),
+                                                    self.framebuffer_scale);
         opengl::set_viewport(x, y, width, height);
     }
 
+    /// Sets the number of framebuffer pixels per logical unit
+    ///
+    /// On high-DPI displays the framebuffer is bigger than the window, an
+    /// 800x600 window can have a 1600x1200 framebuffer, for a scale of 2.
+    /// With SDL, the scale is the ratio between `Window::drawable_size`
+    /// and `Window::size`. The viewport, clip rectangles and picking
+    /// coordinates stay in logical units, only the OpenGL calls get pixels.
+    pub fn set_framebuffer_scale(&mut self, framebuffer_scale: f32) {
+        self.framebuffer_scale = framebuffer_scale;
+        let (x, y, width, height) = self.viewport;
+        self.set_viewport(x, y, width, height);
+    }
+
+    pub fn framebuffer_scale(&self) -> f32 {
+        self.framebuffer_scale
+    }
+
+    /// Returns an orthographic projection for 2D rendering in screen
+    /// coordinates
+    ///
+    /// The origin is the top-left corner and the Y axis points down. The
+    /// visible depth range goes from 0 to -100.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::GLSceneRenderer;
+    ///
+    /// let projection = GLSceneRenderer::screen_ortho(800.0, 600.0);
+    /// let top_left = projection * nalgebra_glm::vec4(0.0, 0.0, 0.0, 1.0);
+    /// let bottom_right = projection * nalgebra_glm::vec4(800.0, 600.0, 0.0, 1.0);
+    /// assert_eq!((top_left[0], top_left[1]), (-1.0, 1.0));
+    /// assert_eq!((bottom_right[0], bottom_right[1]), (1.0, -1.0));
+    /// ```
+    pub fn screen_ortho(width: f32, height: f32) -> nalgebra_glm::Mat4 {
+        nalgebra_glm::ortho(0.0, width, height, 0.0, 0.0, 100.0)
+    }
+
+    /// Sets the projection the scene is rendered with
+    ///
+    /// It is uploaded to the `transform` uniform of the shader programs
+    /// before drawing, and used to map screen coordinates back to world
+    /// coordinates when picking nodes. Until a projection is set, the
+    /// `transform` uniform is left as it is.
+    pub fn set_projection(&mut self, projection: nalgebra_glm::Mat4) {
+        self.projection = projection;
+        self.upload_projection = true;
+    }
+
+    /// Renders a scene with the given projection, which becomes the one
+    /// used by the following calls to render_scene
+    pub fn render_scene_with_projection(&mut self, scene: &SceneGraph,
+                                        projection: &nalgebra_glm::Mat4) {
+        self.set_projection(*projection);
+        self.render_scene(scene);
+    }
+
+    /// Returns the identifier of the topmost node rendered at the given
+    /// screen coordinates
+    ///
+    /// The coordinates are in logical units, relative to the top-left
+    /// corner of the viewport. The node with the highest z coordinate wins, nodes at
+    /// the same depth are ordered by submission so the last rendered one
+    /// is picked.
+    pub fn pick(&self, screen_x: f32, screen_y: f32) -> Option<NodeIdentifier> {
+        let (_, _, width, height) = self.viewport;
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+
+        let ndc_x = 2.0 * screen_x / width as f32 - 1.0;
+        let ndc_y = 1.0 - 2.0 * screen_y / height as f32;
+        let inverse_projection = nalgebra_glm::inverse(&self.projection);
+        let world = inverse_projection * nalgebra_glm::vec4(ndc_x, ndc_y, 0.0, 1.0);
+        let x = world[0] / world[3];
+        let y = world[1] / world[3];
+
+        let mut picked: Option<&PickRegion> = None;
+        for region in self.pick_regions.iter() {
+            let (min, max) = (region.min, region.max);
+            if x < min.0 || x > max.0 || y < min.1 || y > max.1 {
+                continue;
+            }
+
+            if picked.map_or(true, |p| max.2 >= p.max.2) {
+                picked = Some(region);
+            }
+        }
+
+        picked.map(|region| region.identifier.clone())
+    }
+
+    /// Applies the attributes set for a node to the meshes generated for
+    /// it since `first_mesh`, and the transform accumulated from the root
+    fn apply_node_attributes(&mut self, identifier: &str, first_mesh: usize,
+                             clip_rect: Option<ClipRect>,
+                             transform: Option<&nalgebra_glm::Mat4>) {
+        if let Some(shader_identifier) = self.node_shaders.get(identifier) {
+            for mesh in self.pending_meshes[first_mesh..].iter_mut() {
+                mesh.attributes.shader_identifier = Some(shader_identifier.clone());
+            }
+        }
+
+        if clip_rect.is_some() {
+            for mesh in self.pending_meshes[first_mesh..].iter_mut() {
+                mesh.attributes.clip_rect = clip_rect;
+            }
+        }
+
+        if let Some(&blend_mode) = self.node_blend_modes.get(identifier) {
+            for mesh in self.pending_meshes[first_mesh..].iter_mut() {
+                mesh.attributes.blend_mode = Some(blend_mode);
+            }
+        }
+
+        if let Some(transform) = transform {
+            for mesh in self.pending_meshes[first_mesh..].iter_mut() {
+                mesh.transform(transform);
+            }
+        }
+    }
+
+    /// Records the extent of the meshes generated for a node since
+    /// `first_mesh` so the node can be picked
+    fn register_pick_region(&mut self, identifier: &str, first_mesh: usize) {
+        let mut bounds: Option<((f32, f32, f32), (f32, f32, f32))> = None;
+        for mesh in self.pending_meshes[first_mesh..].iter() {
+            if let Some((min, max)) = mesh.bounds() {
+                bounds = Some(match bounds {
+                    Some((bmin, bmax)) =>
+                        ((bmin.0.min(min.0), bmin.1.min(min.1), bmin.2.min(min.2)),
+                         (bmax.0.max(max.0), bmax.1.max(max.1), bmax.2.max(max.2))),
+                    None => (min, max)
+                });
+            }
+        }
+
+        if let Some((min, max)) = bounds {
+            self.pick_regions.push(PickRegion {
+                identifier: identifier.into(),
+                min,
+                max
+            });
+        }
+    }
+
+    /// Sets the store of the shader programs selected by mesh attributes
+    pub fn set_shader_store(&mut self,
+        shader_store: Rc<RefCell<dyn ResourceStore<opengl::ShaderProgram>>>) {
+        self.shader_store = Some(shader_store);
+    }
+
+    /// Draws the node with the given identifier with a shader program of
+    /// the shader store instead of the bound one
+    pub fn set_node_shader(&mut self, identifier: &str, shader_identifier: &str) {
+        self.node_shaders.insert(identifier.into(), shader_identifier.into());
+    }
+
+    /// Draws the node with the given identifier with the bound shader
+    /// program
+    pub fn remove_node_shader(&mut self, identifier: &str) {
+        self.node_shaders.remove(identifier);
+    }
+
+    /// Sets the texture unit the textures of sprites and rectangles are
+    /// bound to
+    pub fn set_sprite_texture_unit(&mut self, texture_unit: TextureUnit) {
+        self.sprite_texture_unit = texture_unit;
+    }
+
+    /// Sets the texture unit the font atlases are bound to
+    pub fn set_font_texture_unit(&mut self, texture_unit: TextureUnit) {
+        self.font_texture_unit = texture_unit;
+    }
+
+    /// Sets the texture unit palettes are bound to, unit 1 with a
+    /// "palette" sampler by default
+    pub fn set_palette_texture_unit(&mut self, texture_unit: TextureUnit) {
+        self.palette_texture_unit = texture_unit;
+    }
+
+    /// Clips the node with the given identifier and its children to a
+    /// rectangle of the viewport
+    ///
+    /// Clip rectangles nest: the children of a clipped node are clipped to
+    /// the intersection of their own rectangle and the ones of their
+    /// ancestors.
+    pub fn set_node_clip(&mut self, identifier: &str, clip_rect: ClipRect) {
+        self.node_clips.insert(identifier.into(), clip_rect);
+    }
+
+    /// Removes the clip rectangle of the node with the given identifier
+    pub fn remove_node_clip(&mut self, identifier: &str) {
+        self.node_clips.remove(identifier);
+    }
+
+    /// Sets the blend mode of the node with the given identifier
+    ///
+    /// Nodes without blend mode are drawn without blending when blend
+    /// passes are enabled.
+    pub fn set_node_blend_mode(&mut self, identifier: &str,
+                               blend_mode: opengl::BlendMode) {
+        self.node_blend_modes.insert(identifier.into(), blend_mode);
+    }
+
+    /// Removes the blend mode of the node with the given identifier
+    pub fn remove_node_blend_mode(&mut self, identifier: &str) {
+        self.node_blend_modes.remove(identifier);
+    }
+
+    /// Sets whether opaque and transparent meshes are drawn in separate
+    /// passes
+    ///
+    /// Meshes are transparent if they have a blend mode or are text.
+    /// Opaque meshes are drawn first, front to back with blending disabled
+    /// and depth testing enabled, so that hidden fragments are rejected
+    /// early.
+    /// Transparent meshes are drawn afterwards, back to front without
+    /// depth writes. A higher z is closer to the viewer, as with
+    /// `screen_ortho`. The framebuffer needs a depth buffer, which is
+    /// cleared with the color buffer.
+    ///
+    /// In both passes, meshes are only batched with the meshes drawn right
+    /// before them, to keep their order.
+    pub fn set_blend_passes(&mut self, blend_passes: bool) {
+        self.blend_passes = blend_passes;
+    }
+
+    /// Sets whether meshes are depth tested using their vertices z
+    ///
+    /// A higher z is closer to the viewer, as with `screen_ortho`. Meshes
+    /// at the same depth are drawn in submission order, the last one on
+    /// top, so they are only batched with the meshes submitted right
+    /// before them. The framebuffer needs a depth buffer, which is cleared
+    /// with the color buffer.
+    pub fn enable_depth_test(&mut self, enabled: bool) {
+        self.depth_test = enabled;
+    }
+
+    /// Sets the style of the text node with the given identifier
+    pub fn set_text_style(&mut self, identifier: &str, style: style::TextStyle) {
+        self.text_styles.insert(identifier.into(), style);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Removes the style of the text node with the given identifier
+    pub fn remove_text_style(&mut self, identifier: &str) {
+        self.text_styles.remove(identifier);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Sets the style of the rectangle node with the given identifier
+    pub fn set_rectangle_style(&mut self, identifier: &str,
+                               style: style::RectangleStyle) {
+        self.rectangle_styles.insert(identifier.into(), style);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Removes the style of the rectangle node with the given identifier
+    pub fn remove_rectangle_style(&mut self, identifier: &str) {
+        self.rectangle_styles.remove(identifier);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Sets the style of the line node with the given identifier
+    ///
+    /// Dash lengths are measured in pixels using the projection and the
+    /// viewport, so that dashes keep the same size on screen.
+    pub fn set_line_style(&mut self, identifier: &str, style: style::LineStyle) {
+        self.line_styles.insert(identifier.into(), style);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Removes the style of the line node with the given identifier
+    pub fn remove_line_style(&mut self, identifier: &str) {
+        self.line_styles.remove(identifier);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Sets the rotation and scale of the sprite node with the given
+    /// identifier
+    pub fn set_sprite_transform(&mut self, identifier: &str,
+                                transform: style::SpriteTransform) {
+        self.sprite_transforms.insert(identifier.into(), transform);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Removes the rotation and scale of the sprite node with the given
+    /// identifier
+    pub fn remove_sprite_transform(&mut self, identifier: &str) {
+        self.sprite_transforms.remove(identifier);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Sets the position, rotation and scale of the node with the given
+    /// identifier
+    ///
+    /// The transform is relative to the parent node, moving a node moves
+    /// its children. The vertices are transformed when the node is
+    /// submitted, after the sprite transform.
+    pub fn set_node_transform(&mut self, identifier: &str,
+                              transform: style::NodeTransform) {
+        self.node_transforms.insert(identifier.into(), transform);
+    }
+
+    /// Removes the transform of the node with the given identifier, which
+    /// is then drawn at the origin
+    pub fn remove_node_transform(&mut self, identifier: &str) {
+        self.node_transforms.remove(identifier);
+    }
+
+    /// Returns the transform of the node with the given identifier
+    pub fn node_transform(&self, identifier: &str) -> Option<&style::NodeTransform> {
+        self.node_transforms.get(identifier)
+    }
+
+    /// Replaces the texture of the sprite node with the given identifier
+    ///
+    /// This changes the texture of a sprite from one frame to the next
+    /// without rebuilding its scene node. The texture identifier is part
+    /// of the attributes of the sprite mesh, so the sprite moves to the
+    /// batch of its new texture and that texture is bound to draw it.
+    /// Changes made to the node itself are picked up the same way, except
+    /// with mesh caching where the node must be marked dirty.
+    pub fn set_sprite_texture(&mut self, identifier: &str, texture_identifier: &str) {
+        self.sprite_textures.insert(identifier.into(), texture_identifier.into());
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Goes back to the texture of the sprite node with the given
+    /// identifier
+    pub fn remove_sprite_texture(&mut self, identifier: &str) {
+        self.sprite_textures.remove(identifier);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Sets the region of its texture the sprite node with the given
+    /// identifier is drawn with
+    ///
+    /// Changing the region from frame to frame plays an animation out of a
+    /// sprite sheet, such as an atlas packed by `atlas::pack`.
+    pub fn set_sprite_region(&mut self, identifier: &str, region: style::TextureRegion) {
+        self.sprite_regions.insert(identifier.into(), region);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Draws the sprite node with the given identifier with its whole
+    /// texture
+    pub fn remove_sprite_region(&mut self, identifier: &str) {
+        self.sprite_regions.remove(identifier);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Sets the palette of the sprite node with the given identifier
+    ///
+    /// The texture of the sprite is then read as palette indices from its
+    /// red channel, and the palette texture is bound alongside it. The
+    /// sprite needs a shader doing the lookup, like data/palette.frag, set
+    /// with `set_node_shader`. Both textures should use gl::NEAREST
+    /// filtering so that indices aren't interpolated.
+    pub fn set_sprite_palette(&mut self, identifier: &str,
+                              palette_texture_identifier: &str) {
+        self.sprite_palettes.insert(identifier.into(), palette_texture_identifier.into());
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Removes the palette of the sprite node with the given identifier
+    pub fn remove_sprite_palette(&mut self, identifier: &str) {
+        self.sprite_palettes.remove(identifier);
+        self.mark_node_dirty(identifier);
+    }
+
+    /// Returns the length in pixels of a segment once projected, or its
+    /// length in world units if no viewport has been set
+    fn screen_length(&self, a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
+        let (_, _, width, height) = self.viewport;
+        if width <= 0 || height <= 0 {
+            let (dx, dy, dz) = (b.0 - a.0, b.1 - a.1, b.2 - a.2);
+            return (dx * dx + dy * dy + dz * dz).sqrt();
+        }
+
+        let pa = self.projection * nalgebra_glm::vec4(a.0, a.1, a.2, 1.0);
+        let pb = self.projection * nalgebra_glm::vec4(b.0, b.1, b.2, 1.0);
+        let dx = (pb[0] / pb[3] - pa[0] / pa[3]) * width as f32 / 2.0;
+        let dy = (pb[1] / pb[3] - pa[1] / pa[3]) * height as f32 / 2.0;
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// Sets whether nodes sharing an identifier are all rendered
+    ///
+    /// By default a node is rendered once per identifier: once a node has
+    /// been rendered, any other node with the same identifier is skipped
+    /// along with its children. With instancing enabled, a node is
+    /// rendered once per path from the root instead, so the same widget
+    /// can be instanced under several parents. A node is still skipped if
+    /// one of its ancestors has the same identifier, which prevents
+    /// infinite recursion.
+    pub fn set_node_instancing(&mut self, node_instancing: bool) {
+        self.node_instancing = node_instancing;
+    }
+
+    /// Sets whether the meshes generated for each node are kept across
+    /// frames
+    ///
+    /// With caching enabled, a node is only turned into meshes the first
+    /// time it is rendered, or after `mark_node_dirty` has been called for
+    /// it. This suits static scene graphs, at the cost of keeping a copy of
+    /// the vertices and indices of every rendered node in memory until it
+    /// is marked dirty or the cache is cleared. Changing the style of a
+    /// node marks it dirty, but the renderer can't see changes to the
+    /// nodes themselves. Dashed lines keep the dashes computed with the
+    /// projection of the frame they were cached in. Disabling caching
+    /// clears the cache.
+    ///
+    /// With node instancing, the meshes are cached once per path from the
+    /// root, so nodes sharing an identifier keep their own meshes.
+    pub fn set_mesh_caching(&mut self, mesh_caching: bool) {
+        self.mesh_caching = mesh_caching;
+        if !mesh_caching {
+            self.clear_mesh_cache();
+        }
+    }
+
+    /// Discards the cached meshes of the node with the given identifier so
+    /// that they are generated again the next time it is rendered
+    ///
+    /// The meshes of all the instances of the node are discarded.
+    pub fn mark_node_dirty(&mut self, identifier: &str) {
+        self.mesh_cache.remove(identifier);
+    }
+
+    /// Discards the cached meshes of every node
+    pub fn clear_mesh_cache(&mut self) {
+        self.mesh_cache.clear();
+    }
+
+    /// Queues a rectangle whose top-left corner is at (x, y)
+    ///
+    /// Like the other draw functions, this doesn't require a scene node.
+    /// The rectangle is drawn with the projection on the next call to
+    /// `render` or render_scene.
+    pub fn draw_rect(&mut self, x: f32, y: f32, width: f32, height: f32,
+                     color: RGBColor) {
+        let mut mesh = Mesh::new(MeshAttributes::defaults());
+        mesh.add_quad(&[
+            Vertex::with_values((x, y, 0.0), color, (0.0, 0.0)),
+            Vertex::with_values((x, y + height, 0.0), color, (0.0, 1.0)),
+            Vertex::with_values((x + width, y + height, 0.0), color, (1.0, 1.0)),
+            Vertex::with_values((x + width, y, 0.0), color, (1.0, 0.0))
+        ]);
+        self.pending_meshes.push(mesh);
+    }
+
+    /// Queues a line between two points
+    pub fn draw_line(&mut self, a: (f32, f32, f32), b: (f32, f32, f32),
+                     color: RGBColor) {
+        let mesh_attributes = MeshAttributesBuilder::new()
+            .draw_mode(gl::LINES)
+            .build();
+        let mut mesh = Mesh::new(mesh_attributes);
+        mesh.add_vertices(&[
+            Vertex::with_values(a, color, (0.0, 0.0)),
+            Vertex::with_values(b, color, (0.0, 0.0))
+        ]);
+        mesh.add_indices(&[0, 1]);
+        self.pending_meshes.push(mesh);
+    }
+
+    /// Queues a text whose top-left corner is at (x, y), using the font of
+    /// the font store with the given identifier
+    pub fn draw_text(&mut self, text: &str, x: f32, y: f32, font_identifier: &str) {
+        let first_mesh = self.pending_meshes.len();
+        let text = tuber::graphics::Text::new(text.into(), font_identifier.into());
+        self.render_text_node("", &text);
+        for mesh in self.pending_meshes[first_mesh..].iter_mut() {
+            mesh.translate((x, y, 0.0));
+        }
+    }
+
+    /// Returns the world space bounding box of the geometry of a node, once
+    /// transformed by the given matrix
+    ///
+    /// The bounding box encloses the meshes the node is rendered with, its
+    /// styles included. Nodes that aren't rendered have no bounding box.
+    pub fn node_world_bounds(&mut self, node: &SceneNode,
+                             transform: &nalgebra_glm::Mat4) -> Option<Aabb> {
+        match node.value() {
+            NodeValue::RectangleNode(_) | NodeValue::LineNode(_) |
+            NodeValue::SpriteNode(_) | NodeValue::TextNode(_) => {},
+            _ => return None
+        }
+
+        let first_mesh = self.pending_meshes.len();
+        self.render_scene_node(node);
+        let meshes: Vec<Mesh> = self.pending_meshes.drain(first_mesh..).collect();
+        meshes.iter()
+            .filter_map(|mesh| mesh.bounds())
+            .map(|(min, max)| Aabb::new(min, max).transformed(transform))
+            .fold(None, |bounds: Option<Aabb>, mesh_bounds| match bounds {
+                Some(bounds) => Some(bounds.union(&mesh_bounds)),
+                None => Some(mesh_bounds)
+            })
+    }
+
     /// Renders a scene node
     fn render_scene_node(&mut self, scene_node: &SceneNode) {
         match scene_node.value() {
-            NodeValue::RectangleNode(rectangle) => self.render_rectangle_node(rectangle),
-            NodeValue::LineNode(line) => self.render_line_node(line),
-            NodeValue::SpriteNode(sprite) => self.render_sprite_node(sprite),
-            NodeValue::TextNode(text) => self.render_text_node(text),
-            _ => println!("Node value of {} isn't renderable", scene_node.identifier())
+            NodeValue::RectangleNode(rectangle) =>
+                self.render_rectangle_node(scene_node.identifier(), rectangle),
+            NodeValue::LineNode(line) =>
+                self.render_line_node(scene_node.identifier(), line),
+            NodeValue::SpriteNode(sprite) =>
+                self.render_sprite_node(scene_node.identifier(), sprite),
+            NodeValue::TextNode(text) => self.render_text_node(scene_node.identifier(), text),
+            _ => if self.verbose {
+                eprintln!("Node value of {} isn't renderable", scene_node.identifier());
+            }
         }
     }
 
+    /// Renders the subtree rooted at the given node
+    ///
+    /// The frame isn't cleared, which allows to render several subtrees
+    /// into different viewports of the same frame. Only the nodes of the
+    /// last rendered subtree can be picked.
+    pub fn render_node(&mut self, root: &SceneNode) {
+        self.pick_regions.clear();
+        self.submit_node(root);
+        self.render();
+    }
+
+    /// Renders the subtree rooted at the given node into a region of the
+    /// frame with its own projection
+    ///
+    /// Calling this once per region renders split-screen frames. Every
+    /// binding made while drawing is undone before returning, so regions
+    /// don't affect each other. The viewport and the projection stay set
+    /// afterwards, picking only works in the last rendered region.
+    pub fn render_node_in_viewport(&mut self, root: &SceneNode,
+                                   viewport: (i32, i32, i32, i32),
+                                   projection: &nalgebra_glm::Mat4) {
+        let (x, y, width, height) = viewport;
+        self.set_viewport(x, y, width, height);
+        self.set_projection(*projection);
+        self.render_node(root);
+    }
+
+    /// Renders a scene graph once per pass, in order
+    ///
+    /// Each pass draws the nodes passing its filter into its target, with
+    /// its projection, viewport and shader. The background isn't drawn by
+    /// passes. The default framebuffer is bound again afterwards, and the
+    /// viewport and projection of the last pass stay set.
+    pub fn render_passes(&mut self, scene: &SceneGraph, passes: &[RenderPass]) {
+        for pass in passes {
+            opengl::bind_framebuffer(pass.render_target().framebuffer_identifier());
+            if let Some((x, y, width, height)) = pass.viewport_rect() {
+                self.set_viewport(x, y, width, height);
+            }
+            self.set_projection(*pass.projection());
+
+            match pass.clear_policy() {
+                ClearPolicy::Keep => {},
+                ClearPolicy::Color(color) => {
+                    opengl::set_clear_color(color.0, color.1, color.2);
+                    opengl::clear(gl::COLOR_BUFFER_BIT);
+                },
+                ClearPolicy::ColorAndDepth(color) => {
+                    opengl::set_clear_color(color.0, color.1, color.2);
+                    opengl::clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                }
+            }
+
+            self.pick_regions.clear();
+            let first_mesh = self.pending_meshes.len();
+            self.submit_filtered_node(scene.root(), &|node| pass.accepts(node));
+            if let Some(shader_identifier) = pass.shader_identifier() {
+                for mesh in self.pending_meshes[first_mesh..].iter_mut() {
+                    mesh.attributes.shader_identifier = Some(shader_identifier.clone());
+                }
+            }
+            self.render();
+        }
+
+        opengl::bind_framebuffer(0);
+        let c = self.clear_color;
+        opengl::set_clear_color(c.0, c.1, c.2);
+    }
+
+    /// Generates the meshes of the subtree rooted at the given node
+    fn submit_node(&mut self, root: &SceneNode) {
+        self.submit_filtered_node(root, &|_| true);
+    }
+
+    /// Generates the meshes of the nodes of the subtree rooted at the given
+    /// node that pass the filter
+    ///
+    /// The children of a node that doesn't pass the filter are still
+    /// visited. The transforms of the nodes are accumulated from the root.
+    fn submit_filtered_node(&mut self, root: &SceneNode,
+                            filter: &dyn Fn(&SceneNode) -> bool) {
+        let mut stack: Vec<(&SceneNode, usize, Option<ClipRect>, Option<nalgebra_glm::Mat4>)> =
+            vec!((root, 0, None, None));
+        let mut visited = HashSet::new();
+        let mut path = vec!();
+
+        while let Some((node, depth, parent_clip, parent_transform)) = stack.pop() {
+            let identifier = node.identifier();
+            path.truncate(depth);
+            let skipped = if self.node_instancing {
+                path.iter().any(|ancestor| *ancestor == identifier)
+            } else {
+                visited.contains(identifier)
+            };
+
+            if skipped {
+                continue;
+            }
+
+            let clip_rect = match (parent_clip, self.node_clips.get(identifier)) {
+                (Some(parent_clip), Some(node_clip)) =>
+                    Some(intersect_clip_rects(parent_clip, *node_clip)),
+                (None, Some(node_clip)) => Some(*node_clip),
+                (parent_clip, None) => parent_clip
+            };
+
+            let transform = match (parent_transform, self.node_transforms.get(identifier)) {
+                (Some(parent_transform), Some(node_transform)) =>
+                    Some(parent_transform * node_transform.matrix()),
+                (None, Some(node_transform)) => Some(node_transform.matrix()),
+                (parent_transform, None) => parent_transform
+            };
+
+            if filter(node) {
+                let first_mesh = self.pending_meshes.len();
+                // Instanced nodes share an identifier, their meshes are
+                // cached per path from the root
+                let cached_meshes = self.mesh_cache.get(identifier)
+                    .and_then(|entries| entries.iter().find(|(ancestors, _)| *ancestors == path));
+                match cached_meshes {
+                    Some((_, meshes)) if self.mesh_caching =>
+                        self.pending_meshes.extend(meshes.iter().cloned()),
+                    _ => {
+                        self.render_scene_node(node);
+                        if self.mesh_caching {
+                            let ancestors = path.iter().map(|ancestor| ancestor.to_string()).collect();
+                            let meshes = self.pending_meshes[first_mesh..].to_vec();
+                            self.mesh_cache.entry(identifier.to_string())
+                                .or_insert_with(Vec::new)
+                                .push((ancestors, meshes));
+                        }
+                    }
+                }
+                self.apply_node_attributes(identifier, first_mesh, clip_rect,
+                                           transform.as_ref());
+                self.register_pick_region(identifier, first_mesh);
+            }
+            visited.insert(identifier);
+            path.push(identifier);
+            for child in node.children() {
+                stack.push((child, depth + 1, clip_rect, transform));
+            }
+        }
+    }
+
+    /// Sets whether each batch is tinted with a distinct color
+    ///
+    /// The vertex colors of the meshes are multiplied by a color of a
+    /// palette chosen from the batch index, which shows the meshes drawn
+    /// together.
+    pub fn set_debug_batches(&mut self, debug_batches: bool) {
+        self.debug_batches = debug_batches;
+    }
+
+    /// Sets whether the nodes that can't be rendered are reported on the
+    /// standard error, disabled by default
+    ///
+    /// Nodes are reported every time they are submitted.
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
+
+    /// Creates the buffers of a batch of meshes with the given attributes
+    ///
+    /// Batches are kept from one frame to the next, creating them while
+    /// loading avoids a hitch the first time meshes with these attributes
+    /// are drawn. The batch uses 16 bits indices, like any batch of less
+    /// than 65536 vertices. Like the other pooled batches, it is destroyed
+    /// if it isn't used within `BATCH_POOL_MAX_IDLE_RENDERS` calls to
+    /// `render`.
+    pub fn preallocate_batch(&mut self, attributes: MeshAttributes) {
+        let mut batch = RenderBatch::new(attributes, Some(IndexType::UnsignedShort));
+        batch.last_render = self.render_count;
+        self.batch_pool.push(batch);
+        self.trim_batch_pool();
+    }
+
+    /// Sets how the meshes are uploaded to the batches
+    pub fn set_upload_strategy(&mut self, upload_strategy: UploadStrategy) {
+        self.upload_strategy = upload_strategy;
+    }
+
+    /// Destroys the batches kept for the next frames
+    pub fn clear_batch_pool(&mut self) {
+        self.batch_pool.clear();
+    }
+
+    /// Returns the number of batches kept for the next frames
+    pub fn batch_pool_size(&self) -> usize {
+        self.batch_pool.len()
+    }
+
+    /// Returns the number of batches drawn by the last call to `render`
+    pub fn drawn_batch_count(&self) -> usize {
+        self.drawn_batch_count
+    }
+
+    /// Destroys the pooled batches that haven't been used recently
+    ///
+    /// Every distinct set of attributes, like a clip rectangle that moves
+    /// each frame, gets its own batch, so the pool must not keep them all.
+    /// Batches idle for more than `BATCH_POOL_MAX_IDLE_RENDERS` renders are
+    /// dropped, then the least recently used ones until the pool fits in
+    /// `BATCH_POOL_CAPACITY`.
+    fn trim_batch_pool(&mut self) {
+        let render_count = self.render_count;
+        self.batch_pool.retain(|batch|
+            render_count - batch.last_render <= GLSceneRenderer::BATCH_POOL_MAX_IDLE_RENDERS);
+
+        if self.batch_pool.len() > GLSceneRenderer::BATCH_POOL_CAPACITY {
+            self.batch_pool.sort_by(|a, b| b.last_render.cmp(&a.last_render));
+            self.batch_pool.truncate(GLSceneRenderer::BATCH_POOL_CAPACITY);
+        }
+    }
+
+    /// Returns an empty batch with the given attributes and index type,
+    /// reusing one of the pool if possible
+    ///
+    /// A batch without index type draws non-indexed meshes.
+    fn acquire_batch(&mut self, attributes: &MeshAttributes,
+                     index_type: Option<IndexType>) -> RenderBatch {
+        let pooled_batch = self.batch_pool.iter()
+            .position(|batch| &batch.mesh_attributes == attributes
+                      && batch.index_type == index_type);
+
+        let mut batch = match pooled_batch {
+            Some(index) => self.batch_pool.swap_remove(index),
+            None => RenderBatch::new(attributes.clone(), index_type)
+        };
+        batch.set_upload_strategy(self.upload_strategy);
+        batch
+    }
+
+    /// Renders a scene graph, without saving the OpenGL state
+    fn render_scene_contents(&mut self, scene: &SceneGraph) {
+        self.measure_frame();
+        self.pick_regions.clear();
+        let background = self.background_mesh();
+        self.submit_node(scene.root());
+
+        if self.retained_mode {
+            if !self.dirty && self.pending_meshes == self.previous_meshes
+                && background == self.previous_background {
+                self.pending_meshes.clear();
+                self.frame_changed = false;
+                return;
+            }
+
+            self.previous_meshes = self.pending_meshes.clone();
+            self.previous_background = background.clone();
+            self.dirty = false;
+            self.frame_changed = true;
+        }
+
+        if self.auto_clear {
+            self.clear_frame();
+        }
+
+        if let Some(background) = background {
+            self.render_background(background);
+        }
+        self.render();
+    }
+
     /// Render the pending meshes
+    ///
+    /// With blend passes enabled, the opaque meshes are drawn before the
+    /// transparent ones, see `set_blend_passes`.
     pub fn render(&mut self) {
-        self.sort_meshes();
-        self.batch_meshes();
+        self.drawn_batch_count = 0;
+        if self.upload_projection {
+            opengl::set_uniform_mat4(PROJECTION_UNIFORM, &self.projection);
+        }
+
+        if !self.blend_passes {
+            if self.depth_test {
+                opengl::set_depth_test(true);
+                opengl::set_depth_func(gl::LEQUAL);
+            }
+
+            // The depth test lets the last mesh drawn at a depth win
+            self.batch_meshes(self.depth_test);
+            self.render_batches();
+
+            if self.depth_test {
+                opengl::set_depth_func(gl::LESS);
+                opengl::set_depth_test(false);
+            }
+            return;
+        }
+
+        let pending_meshes = std::mem::replace(&mut self.pending_meshes, vec!());
+        let (mut opaque_meshes, mut transparent_meshes): (Vec<_>, Vec<_>) =
+            pending_meshes.into_iter()
+                .enumerate()
+                .partition(|(_, mesh)| !mesh.is_transparent());
+
+        // At the same depth, the last submitted mesh is drawn first so
+        // that it stays on top
+        opaque_meshes.sort_by(|(a_index, a), (b_index, b)| {
+            b.depth().partial_cmp(&a.depth())
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b_index.cmp(a_index))
+        });
+        transparent_meshes.sort_by(|(a_index, a), (b_index, b)| {
+            a.depth().partial_cmp(&b.depth())
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a_index.cmp(b_index))
+        });
+
+        let blend_state = opengl::BlendState::current();
+        opengl::set_depth_test(true);
+
+        opengl::disable_blending();
+        self.pending_meshes = opaque_meshes.into_iter().map(|(_, mesh)| mesh).collect();
+        self.batch_meshes(true);
         self.render_batches();
-    }
+        blend_state.restore();
 
-    /// Sorts the meshes in order to batch them
-    fn sort_meshes(&mut self) {
-        self.pending_meshes.sort_by_key(|mesh| mesh.attributes());
+        opengl::set_depth_mask(false);
+        self.pending_meshes = transparent_meshes.into_iter().map(|(_, mesh)| mesh).collect();
+        self.batch_meshes(true);
+        self.render_batches();
+
+        opengl::set_depth_mask(true);
+        opengl::set_depth_test(false);
     }
 
     /// Batches the meshes together
-    fn batch_meshes(&mut self) {
-        for mesh in self.pending_meshes.iter() {
-            if (self.pending_batches.len() == 0) || 
-                (self.pending_batches.last().unwrap().mesh_attributes() != mesh.attributes()) ||
-                (!self.pending_batches.last().unwrap().can_mesh_fit(&mesh)){
-                
-                let mut render_batch = RenderBatch::new(mesh.attributes().clone());
-                if !render_batch.can_mesh_fit(&mesh) {
-                    panic!("Mesh too big for a batch");
+    ///
+    /// Meshes are grouped by attributes, the batches are drawn in the order
+    /// their attributes first appear in the pending meshes. Meshes without
+    /// indices are batched apart from indexed ones, and drawn without an
+    /// element buffer. Empty meshes are skipped. In debug builds, each
+    /// mesh is validated first and an invalid mesh panics.
+    ///
+    /// When a mesh doesn't fit in the open batch with its attributes, a new
+    /// batch is opened.
+    ///
+    /// If the order must be kept, a mesh is only added to the last batch.
+    fn batch_meshes(&mut self, keep_order: bool) {
+        let pending_meshes = std::mem::replace(&mut self.pending_meshes, vec!());
+        #[cfg(debug_assertions)]
+        {
+            for mesh in pending_meshes.iter().filter(|mesh| !mesh.is_empty()) {
+                if let Err(error) = mesh.validate() {
+                    panic!("Invalid mesh: {}", error);
                 }
-
-                render_batch.add_mesh(mesh.clone());
-                self.pending_batches.push(render_batch);
-            } else {
-                self.pending_batches.last_mut().unwrap().add_mesh(mesh.clone());
             }
         }
 
-        self.pending_meshes.clear();
+        let batch_plans = plan_batches(&pending_meshes, keep_order);
+        let mut pending_meshes: Vec<Option<Mesh>> = pending_meshes.into_iter()
+            .map(Some)
+            .collect();
+        for batch_plan in batch_plans {
+            let batch_index = self.pending_batches.len();
+            let mut render_batch = self.acquire_batch(&batch_plan.attributes,
+                                                      batch_plan.index_type);
+            for mesh_index in batch_plan.meshes {
+                let mut mesh = pending_meshes[mesh_index].take()
+                    .expect("A mesh was planned in two batches");
+                if self.debug_batches {
+                    let tint = DEBUG_BATCH_PALETTE[batch_index % DEBUG_BATCH_PALETTE.len()];
+                    mesh.tint(tint);
+                }
+
+                if let Err(error) = render_batch.add_mesh(mesh) {
+                    panic!("{}", error);
+                }
+            }
+            self.pending_batches.push(render_batch);
+        }
     }
 
     /// Renders the batches of meshes
+    ///
+    /// A texture is only bound if it isn't bound to its unit already, the
+    /// textures are unbound once all the batches are drawn. Textures and
+    /// fonts missing from their store are replaced by a magenta texture,
+    /// with a warning the first time. Batches with a blend mode are drawn
+    /// with it, other font batches with the font blending. Either way, the previous blending state is restored
+    /// after the batch, so batches without blending are drawn with it
+    /// disabled unless it was already enabled.
+    ///
+    /// Batches with a shader identifier are drawn with the corresponding
+    /// program of the shader store, the previously used program is used
+    /// again afterwards.
+    ///
+    /// The front face is set to the winding order of each batch, and back
+    /// to gl::CCW once all of them are drawn. Likewise, the scissor test
+    /// is enabled for clipped batches and disabled afterwards.
     fn render_batches(&mut self) {
-        opengl::clear(gl::COLOR_BUFFER_BIT);
+        let default_program = opengl::current_program();
+        let mut bound_textures = HashMap::new();
+        let framebuffer_scale = self.framebuffer_scale;
         for batch in self.pending_batches.iter_mut() {
             let attributes = batch.mesh_attributes();
+            opengl::set_front_face(attributes.front_face());
+            let (viewport_x, viewport_y, _, viewport_height) = self.viewport;
+            opengl::set_scissor(attributes.clip_rect().map(|(x, y, width, height)|
+                scale_rectangle((viewport_x + x, viewport_y + viewport_height - y - height,
+                                 width, height), framebuffer_scale)));
+            let batch_blend_state = attributes.blend_mode().map(|blend_mode| {
+                let blend_state = opengl::BlendState::current();
+                blend_mode.apply();
+                blend_state
+            });
+
+            let custom_shader = match (attributes.shader_identifier(), &self.shader_store) {
+                (Some(shader_identifier), Some(shader_store)) => {
+                    match shader_store.borrow().get(shader_identifier) {
+                        Some(shader_program) => {
+                            shader_program.use_program();
+                            if self.upload_projection {
+                                opengl::set_uniform_mat4(PROJECTION_UNIFORM,
+                                                         &self.projection);
+                            }
+                            true
+                        },
+                        None => false
+                    }
+                },
+                _ => false
+            };
 
             if let Some(font_identifier) = attributes.font_identifier() {
                 let font_store = self.font_store.borrow();
-                let font = font_store.get(font_identifier).unwrap();
-                opengl::enable_font_blending();
-                font.bind_texture();
+                let texture = texture_or_missing(
+                    font_store.get(font_identifier).map(|font| font.texture()),
+                    &format!("font {}", font_identifier),
+                    &mut self.missing_texture,
+                    &mut self.reported_missing_resources);
+                bind_texture_once(texture, &self.font_texture_unit, &mut bound_textures);
+                if batch_blend_state.is_some() {
+                    batch.render();
+                } else {
+                    let blend_state = opengl::BlendState::current();
+                    opengl::enable_font_blending();
+                    batch.render();
+                    blend_state.restore();
+                }
             }
             else if let Some(texture_identifier) = attributes.texture_identifier() {
                 let texture_store = self.texture_store.borrow();
-                let texture = texture_store.get(texture_identifier).unwrap();
-                texture.bind();
+                let texture = texture_or_missing(
+                    texture_store.get(texture_identifier),
+                    &format!("texture {}", texture_identifier),
+                    &mut self.missing_texture,
+                    &mut self.reported_missing_resources);
+                bind_texture_once(texture, &self.sprite_texture_unit, &mut bound_textures);
+                if let Some(palette_identifier) = attributes.palette_identifier() {
+                    let palette = texture_or_missing(
+                        texture_store.get(palette_identifier),
+                        &format!("palette {}", palette_identifier),
+                        &mut self.missing_texture,
+                        &mut self.reported_missing_resources);
+                    bind_texture_once(palette, &self.palette_texture_unit, &mut bound_textures);
+                }
+                batch.render();
+            } else {
+                batch.render();
+            }
+
+            if let Some(blend_state) = batch_blend_state {
+                blend_state.restore();
             }
 
-            batch.render();
+            if custom_shader {
+                opengl::use_program(default_program);
+            }
         }
 
-        self.pending_batches.clear();
+        for (unit, (_, target)) in bound_textures {
+            opengl::set_active_texture_unit(unit);
+            opengl::unbind_texture(target);
+        }
+        opengl::set_active_texture_unit(0);
+
+        opengl::set_front_face(gl::CCW);
+        opengl::set_scissor(None);
+        self.render_count += 1;
+        self.drawn_batch_count += self.pending_batches.len();
+        for mut batch in self.pending_batches.drain(..) {
+            batch.clear();
+            batch.last_render = self.render_count;
+            self.batch_pool.push(batch);
+        }
+        self.trim_batch_pool();
     }
 
-    fn render_rectangle_node(&mut self, rectangle: &tuber::graphics::Rectangle) {
-        let mut mesh = Mesh::new(MeshAttributes::defaults());
+    fn render_rectangle_node(&mut self, identifier: &str,
+                             rectangle: &tuber::graphics::Rectangle) {
+        let rectangle_style = self.rectangle_styles.get(identifier);
+        let filled = rectangle_style.map_or(true, |style| style.is_filled());
+        let stroke_width = rectangle_style.map_or(0.0, |style| style.stroke_thickness());
+        let (corner_radius, corner_segments) = rectangle_style
+            .map_or((0.0, 0), |style| (style.corner_radius(), style.corner_segments()));
+        let (u, v, uw, vh) = rectangle_style
+            .map_or((0.0, 0.0, 1.0, 1.0), |style| style.region());
+
+        let mut attributes_builder = MeshAttributesBuilder::new();
+        if let Some(texture_identifier) = rectangle_style
+            .and_then(|style| style.texture_identifier().as_ref()) {
+            attributes_builder = attributes_builder.texture(texture_identifier);
+        }
+        if !filled && stroke_width <= 0.0 {
+            attributes_builder = attributes_builder.draw_mode(gl::LINES);
+        }
+
+        let mut mesh = Mesh::new(attributes_builder.build());
 
         let c = rectangle.color();
-        let indices = [0, 1, 2, 2, 0, 3];
-        let vertices = [
-            Vertex::with_values((0.0, 0.0, 0.0),
-                                (c.0, c.1, c.2),
-                                (0.0, 0.0)),
-            Vertex::with_values((0.0, rectangle.height(), 0.0),
-                                (c.0, c.1, c.2),
-                                (0.0, 1.0)),
-            Vertex::with_values((rectangle.width(), rectangle.height(), 0.0),
-                                (c.0, c.1, c.2),
-                                (1.0, 1.0)),
-            Vertex::with_values((rectangle.width(), 0.0, 0.0),
+        let (w, h) = (rectangle.width(), rectangle.height());
+        let vertex = |x: f32, y: f32| {
+            let tx = if w != 0.0 { x / w } else { 0.0 };
+            let ty = if h != 0.0 { y / h } else { 0.0 };
+            Vertex::with_values((x, y, 0.0),
                                 (c.0, c.1, c.2),
-                                (1.0, 0.0))
-        ];
+                                (u + uw * tx, v + vh * ty))
+        };
+
+        if filled && corner_radius > 0.0 && corner_segments > 0 {
+            let r = corner_radius.min(w / 2.0).min(h / 2.0);
+            mesh.add_quad(&[vertex(r, 0.0), vertex(r, h),
+                            vertex(w - r, h), vertex(w - r, 0.0)]);
+            mesh.add_quad(&[vertex(0.0, r), vertex(0.0, h - r),
+                            vertex(r, h - r), vertex(r, r)]);
+            mesh.add_quad(&[vertex(w - r, r), vertex(w - r, h - r),
+                            vertex(w, h - r), vertex(w, r)]);
+
+            let corners = [(r, r, std::f32::consts::PI),
+                           (w - r, r, 1.5 * std::f32::consts::PI),
+                           (w - r, h - r, 0.0),
+                           (r, h - r, 0.5 * std::f32::consts::PI)];
+            for &(cx, cy, start_angle) in corners.iter() {
+                let center_index = mesh.vertices().len() as VertexIndex;
+                mesh.add_vertices(&[vertex(cx, cy)]);
+                for i in 0..=corner_segments {
+                    let angle = start_angle + std::f32::consts::FRAC_PI_2
+                        * i as f32 / corner_segments as f32;
+                    mesh.add_vertices(&[vertex(cx + r * angle.cos(), cy + r * angle.sin())]);
+                }
 
-        mesh.add_vertices(&vertices);
-        mesh.add_indices(&indices);
+                for i in 0..corner_segments {
+                    mesh.add_indices(&[center_index,
+                                       center_index + 1 + i,
+                                       center_index + 2 + i]);
+                }
+            }
+        } else if filled {
+            mesh.add_quad(&[vertex(0.0, 0.0), vertex(0.0, h),
+                            vertex(w, h), vertex(w, 0.0)]);
+        } else if stroke_width <= 0.0 {
+            mesh.add_vertices(&[vertex(0.0, 0.0), vertex(0.0, h),
+                                vertex(w, h), vertex(w, 0.0)]);
+            mesh.add_indices(&[0, 1, 1, 2, 2, 3, 3, 0]);
+        } else {
+            let s = stroke_width.min(w / 2.0).min(h / 2.0);
+            mesh.add_quad(&[vertex(0.0, 0.0), vertex(0.0, s),
+                            vertex(w, s), vertex(w, 0.0)]);
+            mesh.add_quad(&[vertex(0.0, h - s), vertex(0.0, h),
+                            vertex(w, h), vertex(w, h - s)]);
+            mesh.add_quad(&[vertex(0.0, s), vertex(0.0, h - s),
+                            vertex(s, h - s), vertex(s, s)]);
+            mesh.add_quad(&[vertex(w - s, s), vertex(w - s, h - s),
+                            vertex(w, h - s), vertex(w, s)]);
+        }
 
         self.pending_meshes.push(mesh);
     }
 
-    fn render_sprite_node(&mut self, sprite: &tuber::graphics::Sprite) {
-        let mesh_attributes = MeshAttributesBuilder::new()
-            .texture(sprite.texture_identifier())
-            .build();
-        let mut mesh = Mesh::new(mesh_attributes);
-       
+    fn render_sprite_node(&mut self, identifier: &str,
+                          sprite: &tuber::graphics::Sprite) {
+        let sprite_texture_identifier: &str = sprite.texture_identifier();
+        let texture_identifier = self.sprite_textures.get(identifier)
+            .map_or(sprite_texture_identifier, |texture_identifier| texture_identifier.as_str());
+        let mut attributes_builder = MeshAttributesBuilder::new()
+            .texture(texture_identifier);
+        if let Some(palette_identifier) = self.sprite_palettes.get(identifier) {
+            attributes_builder = attributes_builder.palette(palette_identifier);
+        }
+        let mut mesh = Mesh::new(attributes_builder.build());
+
+        let size = (sprite.width(), sprite.height());
+        let transform = self.sprite_transforms.get(identifier)
+            .cloned()
+            .unwrap_or_default();
+        let (u, v, uw, vh) = self.sprite_regions.get(identifier)
+            .cloned()
+            .unwrap_or((0.0, 0.0, 1.0, 1.0));
+        let vertex = |x: f32, y: f32, (tx, ty): (f32, f32)| {
+            let (x, y) = transform.apply((x, y), size);
+            Vertex::with_values((x, y, 0.0), (1.0, 1.0, 1.0), (u + uw * tx, v + vh * ty))
+        };
+
         let indices = [0, 1, 2, 2, 0, 3];
         let vertices = [
-            Vertex::with_values((0.0, 0.0, 0.0),
-                                (1.0, 1.0, 1.0),
-                                (0.0, 0.0)),
-            Vertex::with_values((0.0, sprite.height(), 0.0),
-                                (1.0, 1.0, 1.0),
-                                (0.0, 1.0)),
-            Vertex::with_values((sprite.width(), sprite.height(), 0.0),
-                                (1.0, 1.0, 1.0),
-                                (1.0, 1.0)),
-            Vertex::with_values((sprite.width(), 0.0, 0.0),
-                                (1.0, 1.0, 1.0),
-                                (1.0, 0.0))
+            vertex(0.0, 0.0, (0.0, 0.0)),
+            vertex(0.0, size.1, (0.0, 1.0)),
+            vertex(size.0, size.1, (1.0, 1.0)),
+            vertex(size.0, 0.0, (1.0, 0.0))
         ];
 
         mesh.add_vertices(&vertices);
@@ -184,69 +1530,133 @@ impl GLSceneRenderer {
         self.pending_meshes.push(mesh);
     }
 
-    fn render_text_node(&mut self, text: &tuber::graphics::Text) {
+    fn render_text_node(&mut self, identifier: &str, text: &tuber::graphics::Text) {
+        // The store is only borrowed mutably to rasterize missing glyphs,
+        // if it is borrowed elsewhere they are left out of this frame
+        let lacks_glyphs = self.font_store.borrow().get(text.font_identifier())
+            .map_or(false, |font| font.lacks_glyphs(text.text()));
+        if lacks_glyphs {
+            if let Ok(mut font_store) = self.font_store.try_borrow_mut() {
+                if let Some(font) = font_store.get_mut(text.font_identifier()) {
+                    font.add_glyphs(text.text());
+                }
+            }
+        }
+
         let font_store = self.font_store.borrow();
-        let font = font_store.get(text.font_identifier()).unwrap();
+        let font = match font_store.get(text.font_identifier()) {
+            Some(font) => font,
+            None => {
+                // Without the font, the glyphs can't be laid out
+                report_missing_resource(&format!("font {}", text.font_identifier()),
+                                        &mut self.reported_missing_resources);
+                return;
+            }
+        };
+        let text_style = self.text_styles.get(identifier);
+
+        let mesh_attributes = MeshAttributesBuilder::new()
+            .font(text.font_identifier())
+            .build();
+        let mut mesh = Mesh::new(mesh_attributes);
+
+        let (line_height, base) = font_metrics(font);
+        let anchor_offset = match text_style.map(|style| style.anchor()) {
+            Some(style::VerticalAnchor::Baseline) => -base,
+            Some(style::VerticalAnchor::Bottom) => -line_height,
+            Some(style::VerticalAnchor::Top) | None => 0.0
+        };
 
+        let right_to_left = text_style
+            .map_or(false, |style| style.text_direction() == style::TextDirection::RightToLeft);
         let mut cursor_offset = 0.0;
-        for c in text.text().chars() {
+        for (character_index, c) in text.text().chars().enumerate() {
             let character_metadata = if let Some(character) = font.characters().get(&c) {
                 character
             } else {
                 continue;
             };
 
-            let mesh_attributes = MeshAttributesBuilder::new()
-                .font(text.font_identifier())
-                .build();
+            let (scale, baseline_shift) = text_style
+                .map_or((1.0, 0.0), |style| style.scale_and_shift(character_index));
+            if right_to_left {
+                cursor_offset -= character_metadata.x_advance() * scale;
+            }
 
             let tw = font.horizontal_scale();
             let th = font.vertical_scale();
             let x = character_metadata.x_coordinate() / tw;
             let y = -character_metadata.y_coordinate() / th;
-            let y_off = character_metadata.y_offset();
-            let w = character_metadata.width();
-            let h = character_metadata.height();
-
-            let mut mesh = Mesh::new(mesh_attributes);
-            let indices = [0, 1, 2, 2, 0, 3];
-            let vertices = [
-                Vertex::with_values((cursor_offset, y_off, 0.0),
+            let tex_w = character_metadata.width() / tw;
+            let tex_h = character_metadata.height() / th;
+            let y_off = anchor_offset + character_metadata.y_offset() * scale
+                - baseline_shift;
+            let x_off = cursor_offset + character_metadata.x_offset() * scale;
+            let w = character_metadata.width() * scale;
+            let h = character_metadata.height() * scale;
+
+            mesh.add_quad(&[
+                Vertex::with_values((x_off, y_off, 0.0),
                                     (1.0, 1.0, 1.0),
                                     (x, y)),
-                Vertex::with_values((cursor_offset, y_off + h, 0.0),
+                Vertex::with_values((x_off, y_off + h, 0.0),
                                     (1.0, 1.0, 1.0),
-                                    (x, y - h / th)),
-                Vertex::with_values((cursor_offset + w, y_off + h, 0.0),
+                                    (x, y - tex_h)),
+                Vertex::with_values((x_off + w, y_off + h, 0.0),
                                     (1.0, 1.0, 1.0),
-                                    (x + w / tw, y - h / th)),
-                Vertex::with_values((cursor_offset + w, y_off, 0.0),
+                                    (x + tex_w, y - tex_h)),
+                Vertex::with_values((x_off + w, y_off, 0.0),
                                     (1.0, 1.0, 1.0),
-                                    (x + w / tw, y))
-            ];
+                                    (x + tex_w, y))
+            ]);
 
-            cursor_offset += w;
+            if !right_to_left {
+                cursor_offset += character_metadata.x_advance() * scale;
+            }
+        }
 
-            mesh.add_vertices(&vertices);
-            mesh.add_indices(&indices);
+        if mesh.vertices().len() != 0 {
             self.pending_meshes.push(mesh);
         }
     }
 
-    fn render_line_node(&mut self, line: &tuber::graphics::Line) {
+    fn render_line_node(&mut self, identifier: &str, line: &tuber::graphics::Line) {
         let mesh_attributes = MeshAttributesBuilder::new()
             .draw_mode(gl::LINES)
             .build();
         let mut mesh = Mesh::new(mesh_attributes);
 
-        let indices = [0, 1];
-        let vertices = [
-            Vertex::with_values(line.first_point(), (1.0, 1.0, 1.0), (0.0, 0.0)),
-            Vertex::with_values(line.second_point(), (1.0, 1.0, 1.0), (0.0, 0.0))
-        ];
-
-        mesh.add_vertices(&vertices);
-        mesh.add_indices(&indices);
+        let a = line.first_point();
+        let b = line.second_point();
+        let point_at = |t: f32| {
+            (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t, a.2 + (b.2 - a.2) * t)
+        };
+
+        let line_style = self.line_styles.get(identifier).cloned()
+            .unwrap_or(style::LineStyle::Solid);
+        let length = self.screen_length(a, b);
+        match line_style {
+            style::LineStyle::Dashed { dash, gap } if dash > 0.0 && length > 0.0 => {
+                let mut start = 0.0;
+                while start < length {
+                    let end = (start + dash).min(length);
+                    let first_index = mesh.vertices().len() as VertexIndex;
+                    mesh.add_vertices(&[
+                        Vertex::with_values(point_at(start / length), (1.0, 1.0, 1.0), (0.0, 0.0)),
+                        Vertex::with_values(point_at(end / length), (1.0, 1.0, 1.0), (0.0, 0.0))
+                    ]);
+                    mesh.add_indices(&[first_index, first_index + 1]);
+                    start += dash + gap.max(0.0);
+                }
+            },
+            _ => {
+                mesh.add_vertices(&[
+                    Vertex::with_values(a, (1.0, 1.0, 1.0), (0.0, 0.0)),
+                    Vertex::with_values(b, (1.0, 1.0, 1.0), (0.0, 0.0))
+                ]);
+                mesh.add_indices(&[0, 1]);
+            }
+        }
 
         self.pending_meshes.push(mesh);
     }
@@ -254,27 +1664,227 @@ impl GLSceneRenderer {
 
 impl SceneRenderer for GLSceneRenderer {
     fn render_scene(&mut self, scene: &SceneGraph) {
-        use std::collections::HashSet;
+        if self.state_isolation {
+            let mut texture_units = vec!(self.sprite_texture_unit.unit(),
+                                         self.font_texture_unit.unit(),
+                                         self.palette_texture_unit.unit());
+            texture_units.sort();
+            texture_units.dedup();
+            let state = opengl::GLState::current(&texture_units);
+            self.render_scene_contents(scene);
+            state.restore();
+        } else {
+            self.render_scene_contents(scene);
+        }
+    }
+}
 
-        let mut stack = vec!(scene.root());
-        let mut visited = HashSet::new();
+/// Converts a rectangle from logical units to framebuffer pixels
+fn scale_rectangle(rectangle: (i32, i32, i32, i32),
+                   scale: f32) -> (i32, i32, i32, i32) {
+    let scale = |value: i32| (value as f32 * scale).round() as i32;
+    let (x, y, width, height) = rectangle;
+    (scale(x), scale(y), scale(width), scale(height))
+}
 
-        while stack.len() != 0 {
-            if let Some(node) = stack.pop() {
-                if !visited.contains(node.identifier()) {
-                    self.render_scene_node(node);
-                    visited.insert(node.identifier());
-                    for child in node.children() {
-                        stack.push(child);
-                    }
+/// Returns the intersection of two clip rectangles, which is empty if
+/// they don't overlap
+fn intersect_clip_rects(a: ClipRect, b: ClipRect) -> ClipRect {
+    let x = a.0.max(b.0);
+    let y = a.1.max(b.1);
+    let right = (a.0 + a.2).min(b.0 + b.2);
+    let bottom = (a.1 + a.3).min(b.1 + b.3);
+    (x, y, (right - x).max(0), (bottom - y).max(0))
+}
+
+/// Returns the texture found in a store, or the magenta texture standing
+/// for missing ones
+///
+/// The magenta texture is created the first time it is needed.
+fn texture_or_missing<'a>(texture: Option<&'a opengl::Texture>,
+                          resource: &str,
+                          missing_texture: &'a mut Option<opengl::Texture>,
+                          reported_missing_resources: &mut HashSet<String>)
+    -> &'a opengl::Texture {
+    if let Some(texture) = texture {
+        return texture;
+    }
+
+    report_missing_resource(resource, reported_missing_resources);
+    missing_texture.get_or_insert_with(|| {
+        let magenta = [255, 0, 255, 255];
+        let pixels = magenta.iter().cycle().take(2 * 2 * 4).cloned().collect();
+        let image = opengl::DecodedImage::new(2, 2, gl::RGBA, pixels);
+        opengl::Texture::from_image(&image, &opengl::TextureParameters::new())
+    })
+}
+
+/// Prints a warning about a missing resource, once per resource
+fn report_missing_resource(resource: &str,
+                           reported_missing_resources: &mut HashSet<String>) {
+    if reported_missing_resources.insert(resource.into()) {
+        eprintln!("Warning: {} not found", resource);
+    }
+}
+
+/// Binds a texture to a texture unit unless it is already bound to it
+///
+/// The bound textures are recorded by unit with their target.
+fn bind_texture_once(texture: &opengl::Texture, texture_unit: &TextureUnit,
+                     bound_textures: &mut HashMap<gl::types::GLuint,
+                                                  (gl::types::GLuint, gl::types::GLenum)>) {
+    texture_unit.activate();
+    let binding = (texture.identifier(), texture.target());
+    if bound_textures.get(&texture_unit.unit()) != Some(&binding) {
+        texture.bind();
+        bound_textures.insert(texture_unit.unit(), binding);
+    }
+}
+
+/// Returns the line height and the distance from the top of the line to
+/// the baseline of a font
+///
+/// Fonts without metrics get them estimated from the glyphs: the line
+/// height is the lowest glyph bottom and the baseline is the bottom of the
+/// 'x' glyph.
+fn font_metrics(font: &font::Font) -> (f32, f32) {
+    if font.line_height() > 0.0 {
+        return (font.line_height(), font.base());
+    }
+
+    let line_height = font.characters().values()
+        .map(|character| character.y_offset() + character.height())
+        .fold(0.0, f32::max);
+    let base = font.characters().get(&'x')
+        .map_or(line_height, |character| character.y_offset() + character.height());
+
+    (line_height, base)
+}
+
+/// Texture unit a kind of texture is bound to by the renderer
+///
+/// If a sampler uniform is given, it is set to the unit on the shader
+/// program in use before drawing.
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::TextureUnit;
+///
+/// let texture_unit = TextureUnit::new(1).sampler("fontTexture");
+/// assert_eq!(texture_unit.unit(), 1);
+/// assert_eq!(texture_unit.sampler_uniform(), &Some("fontTexture".to_string()));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureUnit {
+    unit: gl::types::GLuint,
+    sampler_uniform: Option<String>
+}
+
+impl TextureUnit {
+    pub fn new(unit: gl::types::GLuint) -> TextureUnit {
+        TextureUnit {
+            unit,
+            sampler_uniform: None
+        }
+    }
+
+    /// Sets the name of the sampler uniform reading the unit
+    pub fn sampler(mut self, sampler_uniform: &str) -> TextureUnit {
+        self.sampler_uniform = Some(sampler_uniform.into());
+        self
+    }
+
+    pub fn unit(&self) -> gl::types::GLuint {
+        self.unit
+    }
+
+    pub fn sampler_uniform(&self) -> &Option<String> {
+        &self.sampler_uniform
+    }
+
+    /// Makes the unit the active one and points the sampler uniform to it
+    fn activate(&self) {
+        opengl::set_active_texture_unit(self.unit);
+        if let Some(sampler_uniform) = &self.sampler_uniform {
+            opengl::set_uniform_int(sampler_uniform, self.unit as gl::types::GLint);
+        }
+    }
+}
+
+/// Axis-aligned bounding box
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::Aabb;
+///
+/// let aabb = Aabb::new((0.0, 0.0, 0.0), (10.0, 20.0, 0.0));
+/// let translation = nalgebra_glm::translation(&nalgebra_glm::vec3(5.0, 0.0, 0.0));
+/// let moved = aabb.transformed(&translation);
+/// assert_eq!(moved.min(), (5.0, 0.0, 0.0));
+/// assert_eq!(moved.max(), (15.0, 20.0, 0.0));
+/// assert_eq!(aabb.union(&moved).max(), (15.0, 20.0, 0.0));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    min: (f32, f32, f32),
+    max: (f32, f32, f32)
+}
+
+impl Aabb {
+    pub fn new(min: (f32, f32, f32), max: (f32, f32, f32)) -> Aabb {
+        Aabb {
+            min,
+            max
+        }
+    }
+
+    pub fn min(&self) -> (f32, f32, f32) {
+        self.min
+    }
+
+    pub fn max(&self) -> (f32, f32, f32) {
+        self.max
+    }
+
+    /// Returns the smallest box enclosing both boxes
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: (self.min.0.min(other.min.0),
+                  self.min.1.min(other.min.1),
+                  self.min.2.min(other.min.2)),
+            max: (self.max.0.max(other.max.0),
+                  self.max.1.max(other.max.1),
+                  self.max.2.max(other.max.2))
+        }
+    }
+
+    /// Returns the box enclosing the corners of this one once transformed
+    pub fn transformed(&self, transform: &nalgebra_glm::Mat4) -> Aabb {
+        let mut corners = vec!();
+        for &x in [self.min.0, self.max.0].iter() {
+            for &y in [self.min.1, self.max.1].iter() {
+                for &z in [self.min.2, self.max.2].iter() {
+                    let p = transform * nalgebra_glm::vec4(x, y, z, 1.0);
+                    let p = (p[0] / p[3], p[1] / p[3], p[2] / p[3]);
+                    corners.push(Aabb::new(p, p));
                 }
             }
         }
 
-        self.render();
+        let first_corner = corners[0];
+        corners.iter().fold(first_corner, |aabb, corner| aabb.union(corner))
     }
 }
 
+/// Extent of a rendered node, used for picking
+struct PickRegion {
+    identifier: NodeIdentifier,
+    min: (f32, f32, f32),
+    max: (f32, f32, f32)
+}
+
 /// Builder for MeshAttributes
 ///
 /// # Examples
@@ -293,7 +1903,12 @@ impl SceneRenderer for GLSceneRenderer {
 pub struct MeshAttributesBuilder {
     texture_identifier: Option<String>,
     font_identifier: Option<String>,
-    draw_mode: gl::types::GLenum
+    shader_identifier: Option<String>,
+    draw_mode: gl::types::GLenum,
+    front_face: gl::types::GLenum,
+    clip_rect: Option<ClipRect>,
+    palette_identifier: Option<String>,
+    blend_mode: Option<opengl::BlendMode>
 }
 
 impl MeshAttributesBuilder {
@@ -301,7 +1916,12 @@ impl MeshAttributesBuilder {
         MeshAttributesBuilder { 
             texture_identifier: None,
             font_identifier: None,
-            draw_mode: gl::TRIANGLES
+            shader_identifier: None,
+            draw_mode: gl::TRIANGLES,
+            front_face: gl::CCW,
+            clip_rect: None,
+            palette_identifier: None,
+            blend_mode: None
         }
     }
 
@@ -317,95 +1937,245 @@ impl MeshAttributesBuilder {
         self
     }
 
+    /// Sets the shader program drawing the mesh instead of the bound one
+    pub fn shader(mut self, shader_identifier: &str)
+        -> MeshAttributesBuilder {
+        self.shader_identifier = Some(shader_identifier.into());
+        self
+    }
+
     pub fn draw_mode(mut self, draw_mode: gl::types::GLenum)
         -> MeshAttributesBuilder {
         self.draw_mode = draw_mode;
         self
     }
 
+    /// Sets the winding order of the front faces of the mesh, gl::CCW by
+    /// default
+    pub fn front_face(mut self, front_face: gl::types::GLenum)
+        -> MeshAttributesBuilder {
+        self.front_face = front_face;
+        self
+    }
+
+    /// Clips the mesh to a rectangle of the viewport
+    pub fn clip_rect(mut self, clip_rect: ClipRect)
+        -> MeshAttributesBuilder {
+        self.clip_rect = Some(clip_rect);
+        self
+    }
+
+    /// Sets the palette looked up with the indices stored in the texture
+    pub fn palette(mut self, palette_identifier: &str)
+        -> MeshAttributesBuilder {
+        self.palette_identifier = Some(palette_identifier.into());
+        self
+    }
+
+    /// Draws the mesh with blending, as a transparent mesh
+    pub fn blend_mode(mut self, blend_mode: opengl::BlendMode)
+        -> MeshAttributesBuilder {
+        self.blend_mode = Some(blend_mode);
+        self
+    }
+
+    /// Draws the mesh with the given source and destination blending
+    /// factors, as a transparent mesh
+    ///
+    /// This is the same as the `BlendMode::Custom` blend mode, and replaces
+    /// the blend mode set before.
+    pub fn blend(mut self, source: gl::types::GLenum, destination: gl::types::GLenum)
+        -> MeshAttributesBuilder {
+        self.blend_mode = Some(opengl::BlendMode::Custom(source, destination));
+        self
+    }
+
     pub fn build(self) -> MeshAttributes {
         MeshAttributes {
             texture_identifier: self.texture_identifier,
             font_identifier: self.font_identifier,
-            draw_mode: self.draw_mode
+            shader_identifier: self.shader_identifier,
+            draw_mode: self.draw_mode,
+            front_face: self.front_face,
+            clip_rect: self.clip_rect,
+            palette_identifier: self.palette_identifier,
+            blend_mode: self.blend_mode
+        }
+    }
+}
+
+#[derive(Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
+pub struct MeshAttributes {
+    texture_identifier: Option<String>,
+    font_identifier: Option<String>,
+    shader_identifier: Option<String>,
+    draw_mode: gl::types::GLenum,
+    front_face: gl::types::GLenum,
+    clip_rect: Option<ClipRect>,
+    palette_identifier: Option<String>,
+    blend_mode: Option<opengl::BlendMode>
+}
+
+impl MeshAttributes {
+    pub fn defaults() -> MeshAttributes {
+        MeshAttributes {
+            texture_identifier: None,
+            font_identifier: None,
+            shader_identifier: None,
+            draw_mode: gl::TRIANGLES,
+            front_face: gl::CCW,
+            clip_rect: None,
+            palette_identifier: None,
+            blend_mode: None
         }
     }
+
+    pub fn texture_identifier(&self) -> &Option<String> {
+        &self.texture_identifier
+    }
+
+    pub fn font_identifier(&self) -> &Option<String> {
+        &self.font_identifier
+    }
+
+    pub fn shader_identifier(&self) -> &Option<String> {
+        &self.shader_identifier
+    }
+
+    pub fn draw_mode(&self) -> gl::types::GLenum {
+        self.draw_mode
+    }
+
+    pub fn front_face(&self) -> gl::types::GLenum {
+        self.front_face
+    }
+
+    pub fn clip_rect(&self) -> Option<ClipRect> {
+        self.clip_rect
+    }
+
+    pub fn palette_identifier(&self) -> &Option<String> {
+        &self.palette_identifier
+    }
+
+    pub fn blend_mode(&self) -> Option<opengl::BlendMode> {
+        self.blend_mode
+    }
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Clone)]
-pub struct MeshAttributes {
-    texture_identifier: Option<String>,
-    font_identifier: Option<String>,
-    draw_mode: gl::types::GLenum
+/// Type of the indices stored in an element buffer
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum IndexType {
+    UnsignedShort,
+    UnsignedInt
 }
 
-impl MeshAttributes {
-    pub fn defaults() -> MeshAttributes {
-        MeshAttributes {
-            texture_identifier: None,
-            font_identifier: None,
-            draw_mode: gl::TRIANGLES
+impl IndexType {
+    /// Returns the smallest index type able to address the given number
+    /// of vertices
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::IndexType;
+    ///
+    /// assert_eq!(IndexType::for_vertex_count(4), IndexType::UnsignedShort);
+    /// assert_eq!(IndexType::for_vertex_count(65536), IndexType::UnsignedShort);
+    /// assert_eq!(IndexType::for_vertex_count(65537), IndexType::UnsignedInt);
+    /// ```
+    pub fn for_vertex_count(vertex_count: usize) -> IndexType {
+        if vertex_count <= u16::MAX as usize + 1 {
+            IndexType::UnsignedShort
+        } else {
+            IndexType::UnsignedInt
         }
     }
 
-    pub fn texture_identifier(&self) -> &Option<String> {
-        &self.texture_identifier
+    /// Returns the size in bytes of an index
+    pub fn size(&self) -> usize {
+        match self {
+            IndexType::UnsignedShort => std::mem::size_of::<gl::types::GLushort>(),
+            IndexType::UnsignedInt => std::mem::size_of::<gl::types::GLuint>()
+        }
     }
 
-    pub fn font_identifier(&self) -> &Option<String> {
-        &self.font_identifier
+    /// Returns the OpenGL type of an index
+    pub fn gl_type(&self) -> gl::types::GLenum {
+        match self {
+            IndexType::UnsignedShort => gl::UNSIGNED_SHORT,
+            IndexType::UnsignedInt => gl::UNSIGNED_INT
+        }
     }
+}
 
-    pub fn draw_mode(&self) -> gl::types::GLenum {
-        self.draw_mode
-    }
+/// Way the meshes of a batch are uploaded to its buffers
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UploadStrategy {
+    /// Each mesh is written through a mapped range of the buffers when it
+    /// is added
+    MapBuffer,
+    /// The meshes are accumulated in memory and uploaded with one
+    /// glBufferSubData call per buffer when the batch is drawn, which is
+    /// faster on some drivers
+    BufferSubData
 }
 
 /// Batch of meshes with the same attributes
 struct RenderBatch {
     mesh_attributes: MeshAttributes,
+    index_type: Option<IndexType>,
     vao: opengl::VertexArrayObject,
     vbo: opengl::BufferObject,
-    ebo: opengl::BufferObject,
+    ebo: Option<opengl::BufferObject>,
+    upload_strategy: UploadStrategy,
+    staged_vertices: Vec<Vertex>,
+    staged_indices: Vec<VertexIndex>,
     vertex_count: usize,
     index_count: usize,
-    last_index: usize
+    vertex_capacity: usize,
+    index_capacity: usize,
+    last_render: u64
 }
 
 impl RenderBatch {
-    const MAX_BATCH_SIZE: usize = 4000000;
+    const INITIAL_VERTEX_CAPACITY: usize = 1024;
+    const INITIAL_INDEX_CAPACITY: usize = 1536;
 
-    pub fn new(mesh_attributes: MeshAttributes) -> RenderBatch {
+    /// Creates a batch, without element buffer if there is no index type
+    ///
+    /// The buffers start small and grow as meshes are added.
+    pub fn new(mesh_attributes: MeshAttributes,
+               index_type: Option<IndexType>) -> RenderBatch {
         let vao = opengl::VertexArrayObject::new();
-        let vbo = opengl::BufferObject::with_size(gl::ARRAY_BUFFER,
-                                                  RenderBatch::MAX_BATCH_SIZE);
-        let ebo = opengl::BufferObject::with_size(gl::ELEMENT_ARRAY_BUFFER,
-                                                  RenderBatch::MAX_BATCH_SIZE);
+        let vbo = opengl::BufferObject::with_size(
+                gl::ARRAY_BUFFER,
+                RenderBatch::INITIAL_VERTEX_CAPACITY * std::mem::size_of::<Vertex>());
+        let ebo = index_type.map(|index_type| opengl::BufferObject::with_size(
+                gl::ELEMENT_ARRAY_BUFFER,
+                RenderBatch::INITIAL_INDEX_CAPACITY * index_type.size()));
 
         vao.bind();
         vbo.bind();
-        ebo.bind();
-        vao.set_attribute(0, 3, gl::FLOAT, gl::FALSE,
-                          std::mem::size_of::<Vertex>(),
-                          std::ptr::null() as *const gl::types::GLvoid);
-        vao.set_attribute(1, 3, gl::FLOAT, gl::FALSE,
-                          std::mem::size_of::<Vertex>(),
-                          (3 * std::mem::size_of::<f32>()) 
-                          as *const gl::types::GLvoid);
-        vao.set_attribute(2, 2, gl::FLOAT, gl::FALSE,
-                          std::mem::size_of::<Vertex>(),
-                          (6 * std::mem::size_of::<f32>()) 
-                          as *const gl::types::GLvoid);
+        if let Some(ebo) = &ebo {
+            ebo.bind();
+        }
+        vao.configure(&Vertex::layout());
         vao.unbind();
 
         RenderBatch {
             mesh_attributes,
+            index_type,
             vao,
             vbo,
             ebo,
+            upload_strategy: UploadStrategy::MapBuffer,
+            staged_vertices: vec!(),
+            staged_indices: vec!(),
             vertex_count: 0,
             index_count: 0,
-            last_index: 0
+            vertex_capacity: RenderBatch::INITIAL_VERTEX_CAPACITY,
+            index_capacity: RenderBatch::INITIAL_INDEX_CAPACITY,
+            last_render: 0
         }
     }
 
@@ -413,77 +2183,452 @@ impl RenderBatch {
         self.mesh_attributes.clone()
     }
 
+    /// Returns whether a mesh can be added to the batch
+    ///
+    /// The buffers grow to hold the mesh, but the indices of the mesh must
+    /// be representable by the index type of the batch.
     pub fn can_mesh_fit(&self, mesh: &Mesh) -> bool {
-        let mesh_vertex_count = mesh.vertices().len();
-        let vertex_size = std::mem::size_of::<Vertex>();
+        if mesh.is_indexed() != self.index_type.is_some() {
+            return false;
+        }
+
+        !(self.index_type == Some(IndexType::UnsignedShort) &&
+          self.vertex_count + mesh.vertices().len() > u16::MAX as usize + 1)
+    }
 
-        self.vertex_count * vertex_size + mesh_vertex_count * vertex_size
-            < RenderBatch::MAX_BATCH_SIZE
+    /// Grows the buffers of the batch so that they can hold the given
+    /// numbers of vertices and indices in addition to the current ones
+    ///
+    /// A buffer too small is replaced by one at least twice as big, the
+    /// contents already uploaded are copied into it.
+    pub fn reserve(&mut self, additional_vertices: usize, additional_indices: usize) {
+        let mut reallocated = false;
+
+        let required_vertex_capacity = self.vertex_count + additional_vertices;
+        if required_vertex_capacity > self.vertex_capacity {
+            let vertex_size = std::mem::size_of::<Vertex>();
+            let capacity = required_vertex_capacity.max(self.vertex_capacity * 2);
+            let vbo = opengl::BufferObject::with_size(gl::ARRAY_BUFFER, capacity * vertex_size);
+            let uploaded_vertex_count = self.vertex_count - self.staged_vertices.len();
+            self.vbo.copy_data_to(&vbo, uploaded_vertex_count * vertex_size);
+            self.vbo = vbo;
+            self.vertex_capacity = capacity;
+            reallocated = true;
+        }
+
+        let required_index_capacity = self.index_count + additional_indices;
+        if let Some(index_type) = self.index_type {
+            if required_index_capacity > self.index_capacity {
+                let capacity = required_index_capacity.max(self.index_capacity * 2);
+                let ebo = opengl::BufferObject::with_size(gl::ELEMENT_ARRAY_BUFFER,
+                                                          capacity * index_type.size());
+                let uploaded_index_count = self.index_count - self.staged_indices.len();
+                if let Some(previous_ebo) = &self.ebo {
+                    previous_ebo.copy_data_to(&ebo, uploaded_index_count * index_type.size());
+                }
+                self.ebo = Some(ebo);
+                self.index_capacity = capacity;
+                reallocated = true;
+            }
+        }
+
+        if reallocated {
+            // The vertex array object still refers to the previous buffers
+            self.vao.bind();
+            self.vbo.bind();
+            if let Some(ebo) = &self.ebo {
+                ebo.bind();
+            }
+            self.vao.configure(&Vertex::layout());
+            self.vao.unbind();
+        }
+    }
+
+    /// Adds a mesh to the batch
+    ///
+    /// The indices are offset by the number of vertices already in the
+    /// batch, meshes without indices are only added to batches without
+    /// element buffer. With the map buffer strategy, the vertices and the indices
+    /// are each copied into the buffers in one go. Otherwise they are kept
+    /// until the batch is rendered.
+    ///
+    /// The buffers are grown first if needed. An error is returned if the
+    /// mesh can't be added to the batch, which is left as it was.
+    pub fn add_mesh(&mut self, mesh: Mesh) -> Result<(), String> {
+        if mesh.is_empty() {
+            // Nothing would be drawn, and empty ranges can't be mapped
+            return Ok(());
+        }
+
+        if !self.can_mesh_fit(&mesh) {
+            return Err(format!("A mesh of {} vertices and {} indices doesn't fit in the batch",
+                               mesh.vertices().len(), mesh.indices().len()));
+        }
+        self.reserve(mesh.vertices().len(), mesh.indices().len());
+
+        let index_offset = self.vertex_count as VertexIndex;
+        let indices: Vec<VertexIndex> = mesh.indices().iter()
+            .map(|index| index + index_offset)
+            .collect();
+
+        match self.upload_strategy {
+            UploadStrategy::MapBuffer => self.write_mapped(mesh.vertices(), &indices),
+            UploadStrategy::BufferSubData => {
+                self.staged_vertices.extend_from_slice(mesh.vertices());
+                self.staged_indices.extend_from_slice(&indices);
+            }
+        }
+
+        self.vertex_count += mesh.vertices().len();
+        self.index_count += indices.len();
+        Ok(())
     }
 
-    pub fn add_mesh(&mut self, mesh: Mesh) {
-        let mesh_vertex_count = mesh.vertices().len();
-        let mesh_index_count = mesh.indices().len();
+    /// Copies vertices and indices after the ones of the batch through
+    /// mapped buffer ranges
+    fn write_mapped(&self, vertices: &[Vertex], indices: &[VertexIndex]) {
         let vertex_size = std::mem::size_of::<Vertex>();
-        let index_size = std::mem::size_of::<gl::types::GLuint>();
 
         self.vbo.bind();
-        let mut vertex_buffer_pointer = self.vbo
+        let vertex_buffer_pointer = self.vbo
             .map_buffer_range(self.vertex_count * vertex_size,
-                              mesh_vertex_count * vertex_size,
+                              vertices.len() * vertex_size,
                               gl::MAP_WRITE_BIT) as *mut Vertex;
         unsafe {
-            for vertex in mesh.vertices().iter() {
-                vertex_buffer_pointer.write(*vertex);
-                vertex_buffer_pointer = vertex_buffer_pointer.offset(1);
-            }
+            std::slice::from_raw_parts_mut(vertex_buffer_pointer, vertices.len())
+                .copy_from_slice(vertices);
         }
 
         self.vbo.unmap();
         self.vbo.unbind();
 
-        self.ebo.bind();
-        let mut index_buffer_pointer = self.ebo
-            .map_buffer_range(self.index_count * index_size,
-                              mesh_index_count * index_size,
-                              gl::MAP_WRITE_BIT) as *mut gl::types::GLuint;
+        let (ebo, index_type) = match (&self.ebo, self.index_type) {
+            (Some(ebo), Some(index_type)) => (ebo, index_type),
+            _ => return
+        };
 
+        let index_size = index_type.size();
+        ebo.bind();
+        let index_buffer_pointer = ebo
+            .map_buffer_range(self.index_count * index_size,
+                              indices.len() * index_size,
+                              gl::MAP_WRITE_BIT);
         unsafe {
-            let last_index = self.last_index;
-            for index in mesh.indices().iter() {
-                let index_offset = if last_index == 0 {
-                    0
-                } else {
-                    last_index + 1
-                };
-
-                index_buffer_pointer.write(*index + index_offset as u32);
-                if *index + index_offset as u32 > self.last_index as u32 {
-                    self.last_index = (*index + index_offset as u32) as usize;
+            match index_type {
+                IndexType::UnsignedShort => {
+                    let short_indices: Vec<gl::types::GLushort> = indices.iter()
+                        .map(|&index| index as gl::types::GLushort)
+                        .collect();
+                    std::slice::from_raw_parts_mut(
+                        index_buffer_pointer as *mut gl::types::GLushort,
+                        indices.len()).copy_from_slice(&short_indices);
+                },
+                IndexType::UnsignedInt => {
+                    std::slice::from_raw_parts_mut(
+                        index_buffer_pointer as *mut gl::types::GLuint,
+                        indices.len()).copy_from_slice(indices);
                 }
+            }
+        }
+
+        ebo.unmap();
+        ebo.unbind();
+    }
+
+    /// Uploads the vertices and indices kept by the buffer sub data
+    /// strategy, with one call per buffer
+    fn upload_staged(&mut self) {
+        if self.staged_vertices.is_empty() {
+            return;
+        }
 
-                index_buffer_pointer = index_buffer_pointer.offset(1);
+        let vertex_size = std::mem::size_of::<Vertex>();
+        let staged_vertex_count = self.staged_vertices.len();
+        let staged_index_count = self.staged_indices.len();
+        let first_vertex = self.vertex_count - staged_vertex_count;
+        let first_index = self.index_count - staged_index_count;
+
+        self.vbo.bind();
+        self.vbo.update_data(first_vertex * vertex_size,
+                             staged_vertex_count * vertex_size,
+                             self.staged_vertices.as_ptr() as *const gl::types::GLvoid);
+        self.vbo.unbind();
+
+        if let (Some(ebo), Some(index_type)) = (&self.ebo, self.index_type) {
+            ebo.bind();
+            let index_size = index_type.size();
+            match index_type {
+                IndexType::UnsignedShort => {
+                    let short_indices: Vec<gl::types::GLushort> = self.staged_indices.iter()
+                        .map(|&index| index as gl::types::GLushort)
+                        .collect();
+                    ebo.update_data(first_index * index_size,
+                                    staged_index_count * index_size,
+                                    short_indices.as_ptr() as *const gl::types::GLvoid);
+                },
+                IndexType::UnsignedInt => {
+                    ebo.update_data(first_index * index_size,
+                                    staged_index_count * index_size,
+                                    self.staged_indices.as_ptr() as *const gl::types::GLvoid);
+                }
             }
+            ebo.unbind();
         }
 
-        self.ebo.unmap();
-        self.ebo.unbind();
+        self.staged_vertices.clear();
+        self.staged_indices.clear();
+    }
+
+    /// Sets how meshes are uploaded, the batch must be empty
+    pub fn set_upload_strategy(&mut self, upload_strategy: UploadStrategy) {
+        self.upload_strategy = upload_strategy;
+    }
 
-        self.vertex_count += mesh_vertex_count;
-        self.index_count += mesh_index_count;
+    /// Removes the meshes of the batch, keeping its buffers
+    pub fn clear(&mut self) {
+        self.vertex_count = 0;
+        self.index_count = 0;
+        self.staged_vertices.clear();
+        self.staged_indices.clear();
     }
 
     /// Renders the pending meshes
+    ///
+    /// Batches without element buffer are drawn with glDrawArrays. The
+    /// vertex array object is unbound afterwards so that later buffer
+    /// bindings can't modify it
     pub fn render(&mut self) {
-        self.vao.bind();
-        opengl::draw_elements(self.mesh_attributes.draw_mode(),
-                          self.index_count as gl::types::GLsizei,
-                          gl::UNSIGNED_INT,
-                          std::ptr::null() as *const gl::types::GLvoid);
+        if self.vertex_count == 0 {
+            return;
+        }
+
+        self.upload_staged();
+        let _vao_binding = self.vao.bound();
+        match self.index_type {
+            Some(index_type) => opengl::draw_elements(self.mesh_attributes.draw_mode(),
+                                                      self.index_count as gl::types::GLsizei,
+                                                      index_type.gl_type(),
+                                                      std::ptr::null() as *const gl::types::GLvoid),
+            None => opengl::draw_arrays(self.mesh_attributes.draw_mode(),
+                                        0,
+                                        self.vertex_count as gl::types::GLsizei)
+        }
+    }
+}
+
+/// Meshes planned to be drawn by a single batch
+struct BatchPlan {
+    attributes: MeshAttributes,
+    index_type: Option<IndexType>,
+    /// Indices of the meshes in the planned slice, in drawing order
+    meshes: Vec<usize>,
+    vertex_count: usize
+}
+
+impl BatchPlan {
+    /// Returns whether the batch can take a mesh, see
+    /// `RenderBatch::can_mesh_fit`
+    fn can_mesh_fit(&self, mesh: &Mesh) -> bool {
+        !(self.index_type == Some(IndexType::UnsignedShort) &&
+          self.vertex_count + mesh.vertices().len() > u16::MAX as usize + 1)
+    }
+}
+
+/// Plans the batches drawing the given meshes, following the rules of
+/// `GLSceneRenderer::batch_meshes`
+fn plan_batches(meshes: &[Mesh], keep_order: bool) -> Vec<BatchPlan> {
+    let mut vertex_counts: HashMap<(MeshAttributes, bool), usize> = HashMap::new();
+    for mesh in meshes.iter() {
+        *vertex_counts.entry((mesh.attributes(), mesh.is_indexed()))
+            .or_insert(0) += mesh.vertices().len();
+    }
+
+    let mut batch_plans: Vec<BatchPlan> = vec!();
+    let mut open_batches: HashMap<(MeshAttributes, bool), usize> = HashMap::new();
+    for (mesh_index, mesh) in meshes.iter().enumerate().filter(|(_, mesh)| !mesh.is_empty()) {
+        let attributes = mesh.attributes();
+        let batch_key = (attributes.clone(), mesh.is_indexed());
+        let open_batch = open_batches.get(&batch_key)
+            .cloned()
+            .filter(|&index| !keep_order || index + 1 == batch_plans.len())
+            .filter(|&index| batch_plans[index].can_mesh_fit(mesh));
+
+        let batch_index = match open_batch {
+            Some(index) => index,
+            None => {
+                let index_type = if mesh.is_indexed() {
+                    Some(IndexType::for_vertex_count(vertex_counts[&batch_key]))
+                } else {
+                    None
+                };
+                batch_plans.push(BatchPlan {
+                    attributes,
+                    index_type,
+                    meshes: vec!(),
+                    vertex_count: 0
+                });
+                open_batches.insert(batch_key, batch_plans.len() - 1);
+                batch_plans.len() - 1
+            }
+        };
+
+        batch_plans[batch_index].meshes.push(mesh_index);
+        batch_plans[batch_index].vertex_count += mesh.vertices().len();
+    }
+
+    batch_plans
+}
+
+/// Indexed mesh drawn several times in one draw call, once per transform
+///
+/// The vertices and indices of the mesh are uploaded once, the transforms
+/// are stored in a buffer read once per instance. Shaders get the
+/// transform of the instance as a `mat4` at attribute locations 3 to 6,
+/// like `data/instanced.vert`. The textures and the shader program must be
+/// bound before rendering.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tuber_graphics_opengl::{InstancedBatch, Mesh, MeshAttributes, Vertex};
+///
+/// let vertex = |x, y| Vertex::with_values((x, y, 0.0), (1.0, 1.0, 1.0), (x, y));
+/// let mut tile = Mesh::new(MeshAttributes::defaults());
+/// tile.add_quad(&[vertex(0.0, 0.0), vertex(0.0, 1.0), vertex(1.0, 1.0), vertex(1.0, 0.0)]);
+///
+/// let mut tiles = InstancedBatch::new(&tile);
+/// let transforms: Vec<_> = (0..100)
+///     .map(|i| nalgebra_glm::translation(&nalgebra_glm::vec3(i as f32, 0.0, 0.0)))
+///     .collect();
+/// tiles.set_instances(&transforms);
+/// tiles.render();
+/// ```
+pub struct InstancedBatch {
+    mesh_attributes: MeshAttributes,
+    vao: opengl::VertexArrayObject,
+    _vbo: opengl::BufferObject,
+    _ebo: opengl::BufferObject,
+    instance_vbo: opengl::BufferObject,
+    index_count: usize,
+    instance_count: usize
+}
+
+impl InstancedBatch {
+    /// Location of the first column of the instance transforms
+    pub const TRANSFORM_LOCATION: usize = 3;
+
+    /// Creates a batch drawing instances of an indexed mesh, without any
+    /// instance yet
+    pub fn new(mesh: &Mesh) -> InstancedBatch {
+        let vao = opengl::VertexArrayObject::new();
+        let vbo = opengl::BufferObject::new(gl::ARRAY_BUFFER);
+        let ebo = opengl::BufferObject::new(gl::ELEMENT_ARRAY_BUFFER);
+        let instance_vbo = opengl::BufferObject::new(gl::ARRAY_BUFFER);
+
+        vao.bind();
+        vbo.bind();
+        vbo.set_data(mesh.vertices().len() * std::mem::size_of::<Vertex>(),
+                     mesh.vertices().as_ptr() as *const gl::types::GLvoid,
+                     gl::STATIC_DRAW);
+        vao.configure(&Vertex::layout());
+        ebo.bind();
+        ebo.set_data(mesh.indices().len() * std::mem::size_of::<VertexIndex>(),
+                     mesh.indices().as_ptr() as *const gl::types::GLvoid,
+                     gl::STATIC_DRAW);
+
+        // A mat4 attribute takes four consecutive locations, one per column
+        instance_vbo.bind();
+        let column_size = 4 * std::mem::size_of::<f32>();
+        for column in 0..4 {
+            let location = InstancedBatch::TRANSFORM_LOCATION + column;
+            vao.set_attribute(location,
+                              4,
+                              gl::FLOAT,
+                              gl::FALSE,
+                              4 * column_size,
+                              (column * column_size) as *const gl::types::GLvoid);
+            vao.set_attribute_divisor(location, 1);
+        }
+        vao.unbind();
+        instance_vbo.unbind();
+
+        InstancedBatch {
+            mesh_attributes: mesh.attributes(),
+            vao,
+            _vbo: vbo,
+            _ebo: ebo,
+            instance_vbo,
+            index_count: mesh.indices().len(),
+            instance_count: 0
+        }
+    }
+
+    pub fn mesh_attributes(&self) -> &MeshAttributes {
+        &self.mesh_attributes
+    }
+
+    pub fn instance_count(&self) -> usize {
+        self.instance_count
+    }
+
+    /// Replaces the instances with one per transform
+    ///
+    /// The instance buffer is reallocated, which orphans the previous
+    /// instances if they are still being drawn.
+    pub fn set_instances(&mut self, transforms: &[nalgebra_glm::Mat4]) {
+        self.instance_vbo.bind();
+        self.instance_vbo.set_data(transforms.len() * std::mem::size_of::<nalgebra_glm::Mat4>(),
+                                   transforms.as_ptr() as *const gl::types::GLvoid,
+                                   gl::STREAM_DRAW);
+        self.instance_vbo.unbind();
+        self.instance_count = transforms.len();
+    }
+
+    /// Draws every instance of the mesh
+    pub fn render(&self) {
+        if self.instance_count == 0 || self.index_count == 0 {
+            return;
+        }
+
+        let _vao_binding = self.vao.bound();
+        opengl::draw_elements_instanced(self.mesh_attributes.draw_mode(),
+                                        self.index_count as gl::types::GLsizei,
+                                        gl::UNSIGNED_INT,
+                                        std::ptr::null(),
+                                        self.instance_count as gl::types::GLsizei);
+    }
+}
+
+/// Error returned by `Mesh::validate`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MeshError {
+    /// An index doesn't refer to a vertex of the mesh
+    IndexOutOfRange {
+        index: VertexIndex,
+        vertex_count: usize
+    },
+    /// The number of indices doesn't make whole primitives for the draw
+    /// mode
+    IndexCountMismatch {
+        index_count: usize,
+        draw_mode: gl::types::GLenum
+    }
+}
+
+impl std::fmt::Display for MeshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MeshError::IndexOutOfRange { index, vertex_count } =>
+                write!(f, "Index {} is out of range for a mesh of {} vertices",
+                       index, vertex_count),
+            MeshError::IndexCountMismatch { index_count, draw_mode } =>
+                write!(f, "{} indices don't make whole primitives for draw mode {:#x}",
+                       index_count, draw_mode)
+        }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct Mesh {
     vertices: Vec<Vertex>,
     indices: Vec<VertexIndex>,
@@ -536,6 +2681,191 @@ impl Mesh {
         self.indices.extend_from_slice(indices);
     }
 
+    /// Adds a quad to the mesh as two triangles
+    ///
+    /// The vertices are given in order around the quad, the indices are
+    /// offset by the number of vertices already in the mesh.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::{Mesh, MeshAttributes, Vertex};
+    ///
+    /// let vertex = Vertex::with_values((0.0, 0.0, 0.0), (0.0, 0.0, 0.0), (0.0, 0.0));
+    /// let mut mesh = Mesh::new(MeshAttributes::defaults());
+    /// mesh.add_quad(&[vertex; 4]);
+    /// mesh.add_quad(&[vertex; 4]);
+    /// assert_eq!(mesh.vertices().len(), 8);
+    /// assert_eq!(mesh.indices(), &vec!(0, 1, 2, 2, 0, 3, 4, 5, 6, 6, 4, 7));
+    /// ```
+    pub fn add_quad(&mut self, vertices: &[Vertex; 4]) {
+        let first_index = self.vertices.len() as VertexIndex;
+        self.vertices.extend_from_slice(vertices);
+        for index in [0, 1, 2, 2, 0, 3].iter() {
+            self.indices.push(first_index + index);
+        }
+    }
+
+    /// Appends the vertices and indices of another mesh with the same
+    /// attributes
+    ///
+    /// The indices of the other mesh are offset by the number of vertices
+    /// already in the mesh. Meshes with different attributes can't be
+    /// drawn together, an error is returned in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::{Mesh, MeshAttributes, MeshAttributesBuilder, Vertex};
+    ///
+    /// let vertex = Vertex::with_values((0.0, 0.0, 0.0), (0.0, 0.0, 0.0), (0.0, 0.0));
+    /// let mut triangle = Mesh::new(MeshAttributes::defaults());
+    /// triangle.add_vertices(&[vertex; 3]);
+    /// triangle.add_indices(&[0, 1, 2]);
+    ///
+    /// let mut mesh = triangle.clone();
+    /// assert!(mesh.append(&triangle).is_ok());
+    /// assert_eq!(mesh.vertices().len(), 6);
+    /// assert_eq!(mesh.indices(), &vec!(0, 1, 2, 3, 4, 5));
+    ///
+    /// let textured_mesh = Mesh::new(MeshAttributesBuilder::new().texture("a").build());
+    /// assert!(mesh.append(&textured_mesh).is_err());
+    /// ```
+    pub fn append(&mut self, other: &Mesh) -> Result<(), String> {
+        if self.attributes != other.attributes {
+            return Err("Meshes with different attributes can't be merged".into());
+        }
+
+        let index_offset = self.vertices.len() as VertexIndex;
+        self.vertices.extend_from_slice(&other.vertices);
+        self.indices.extend(other.indices.iter().map(|index| index + index_offset));
+        Ok(())
+    }
+
+    /// Returns whether the mesh draws nothing, having no vertices
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::{Mesh, MeshAttributes, Vertex};
+    ///
+    /// let mut mesh = Mesh::new(MeshAttributes::defaults());
+    /// assert!(mesh.is_empty());
+    /// mesh.add_vertices(&[
+    ///     Vertex::with_values((0.0, 0.0, 0.0), (0.0, 0.0, 0.0), (0.0, 0.0))
+    /// ]);
+    /// assert!(!mesh.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Returns whether the mesh has indices
+    ///
+    /// Meshes without indices are drawn with their vertices in order.
+    pub fn is_indexed(&self) -> bool {
+        !self.indices.is_empty()
+    }
+
+    /// Checks that the indices refer to vertices of the mesh and that
+    /// their count is consistent with the draw mode
+    ///
+    /// Meshes drawn as gl::TRIANGLES need a multiple of 3 indices, meshes
+    /// drawn as gl::LINES a multiple of 2. For meshes without indices, the
+    /// vertex count is checked instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::{Mesh, MeshAttributes, MeshError, Vertex};
+    ///
+    /// let vertex = Vertex::with_values((0.0, 0.0, 0.0), (0.0, 0.0, 0.0), (0.0, 0.0));
+    /// let mut mesh = Mesh::new(MeshAttributes::defaults());
+    /// mesh.add_vertices(&[vertex; 3]);
+    /// mesh.add_indices(&[0, 1, 2]);
+    /// assert_eq!(mesh.validate(), Ok(()));
+    ///
+    /// mesh.add_indices(&[0, 3]);
+    /// assert_eq!(mesh.validate(),
+    ///            Err(MeshError::IndexOutOfRange { index: 3, vertex_count: 3 }));
+    /// ```
+    pub fn validate(&self) -> Result<(), MeshError> {
+        let vertex_count = self.vertices.len();
+        if let Some(&index) = self.indices.iter()
+            .find(|&&index| index as usize >= vertex_count) {
+            return Err(MeshError::IndexOutOfRange { index, vertex_count });
+        }
+
+        let draw_mode = self.attributes.draw_mode();
+        let indices_per_primitive = match draw_mode {
+            gl::TRIANGLES => 3,
+            gl::LINES => 2,
+            _ => 1
+        };
+
+        let index_count = if self.is_indexed() {
+            self.indices.len()
+        } else {
+            vertex_count
+        };
+        if index_count % indices_per_primitive != 0 {
+            return Err(MeshError::IndexCountMismatch { index_count, draw_mode });
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the mesh is drawn with blending, like text or
+    /// meshes with a blend mode
+    pub fn is_transparent(&self) -> bool {
+        self.attributes.blend_mode.is_some()
+            || self.attributes.font_identifier.is_some()
+    }
+
+    /// Returns the z coordinate of the center of the mesh
+    fn depth(&self) -> f32 {
+        self.bounds().map_or(0.0, |(min, max)| (min.2 + max.2) / 2.0)
+    }
+
+    /// Moves all the vertices of the mesh by an offset
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::{Mesh, MeshAttributes, Vertex};
+    ///
+    /// let mut mesh = Mesh::new(MeshAttributes::defaults());
+    /// mesh.add_vertices(&[
+    ///     Vertex::with_values((1.0, 2.0, 0.0), (0.0, 0.0, 0.0), (0.0, 0.0))
+    /// ]);
+    /// mesh.translate((10.0, -2.0, 1.0));
+    /// assert_eq!(mesh.vertices()[0].position(), (11.0, 0.0, 1.0));
+    /// ```
+    pub fn translate(&mut self, offset: (f32, f32, f32)) {
+        for vertex in self.vertices.iter_mut() {
+            let p = vertex.position;
+            vertex.position = (p.0 + offset.0, p.1 + offset.1, p.2 + offset.2);
+        }
+    }
+
+    /// Transforms the positions of all the vertices of the mesh by a
+    /// matrix
+    pub fn transform(&mut self, matrix: &nalgebra_glm::Mat4) {
+        for vertex in self.vertices.iter_mut() {
+            let p = vertex.position;
+            let p = matrix * nalgebra_glm::vec4(p.0, p.1, p.2, 1.0);
+            vertex.position = (p.x, p.y, p.z);
+        }
+    }
+
+    /// Multiplies the colors of all the vertices of the mesh by a color
+    pub fn tint(&mut self, color: RGBColor) {
+        for vertex in self.vertices.iter_mut() {
+            let c = vertex.color;
+            vertex.color = (c.0 * color.0, c.1 * color.1, c.2 * color.2);
+        }
+    }
+
     pub fn vertices(&self) -> &Vec<Vertex> {
         &self.vertices
     }
@@ -547,10 +2877,38 @@ impl Mesh {
     pub fn attributes(&self) -> MeshAttributes {
         self.attributes.clone()
     }
+
+    /// Returns the minimum and maximum corners of the mesh's bounding box,
+    /// or None if the mesh has no vertices
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::{Mesh, MeshAttributes, Vertex};
+    ///
+    /// let mut mesh = Mesh::new(MeshAttributes::defaults());
+    /// assert_eq!(mesh.bounds(), None);
+    /// mesh.add_vertices(&[
+    ///     Vertex::with_values((-1.0, 2.0, 0.0), (0.0, 0.0, 0.0), (0.0, 0.0)),
+    ///     Vertex::with_values((3.0, -4.0, 1.0), (0.0, 0.0, 0.0), (0.0, 0.0)),
+    ///     Vertex::with_values((0.0, 5.0, 0.5), (0.0, 0.0, 0.0), (0.0, 0.0))
+    /// ]);
+    /// assert_eq!(mesh.bounds(), Some(((-1.0, -4.0, 0.0), (3.0, 5.0, 1.0))));
+    /// ```
+    pub fn bounds(&self) -> Option<((f32, f32, f32), (f32, f32, f32))> {
+        let first_position = self.vertices.first()?.position();
+        Some(self.vertices.iter().fold((first_position, first_position),
+            |(min, max), vertex| {
+                let p = vertex.position();
+                ((min.0.min(p.0), min.1.min(p.1), min.2.min(p.2)),
+                 (max.0.max(p.0), max.1.max(p.1), max.2.max(p.2)))
+            }))
+    }
 }
 
 /// Represents a vertex in 3D space
-#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Vertex {
     position: (f32, f32, f32),
     color: (f32, f32, f32),
@@ -569,6 +2927,23 @@ impl Vertex {
         }
     }
 
+    /// Returns the layout of a vertex in a vertex buffer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::Vertex;
+    ///
+    /// let layout = Vertex::layout();
+    /// assert_eq!(layout.stride(), std::mem::size_of::<Vertex>());
+    /// ```
+    pub fn layout() -> opengl::VertexLayout {
+        opengl::VertexLayout::new()
+            .attribute(3, gl::FLOAT, gl::FALSE)
+            .attribute(3, gl::FLOAT, gl::FALSE)
+            .attribute(2, gl::FLOAT, gl::FALSE)
+    }
+
     pub fn position(&self) -> (f32, f32, f32) {
         self.position
     }
@@ -581,3 +2956,90 @@ impl Vertex {
         self.texture_coordinates
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tuber::graphics::Sprite;
+
+    /// Store without any resource, the meshes are built without GL calls
+    struct EmptyStore;
+
+    impl<T> ResourceStore<T> for EmptyStore {
+        fn store(&mut self, _resource_file_path: String, _value: T) {}
+        fn remove(&mut self, _resource_file_path: &str) {}
+        fn get(&self, _resource_file_path: &str) -> Option<&T> {
+            None
+        }
+        fn get_mut(&mut self, _resource_file_path: &str) -> Option<&mut T> {
+            None
+        }
+    }
+
+    fn sprite_scene(textures: &[(&str, &str)]) -> SceneGraph {
+        let mut scene = SceneGraph::new();
+        for &(identifier, texture_identifier) in textures.iter() {
+            scene.root_mut().add_child(SceneNode::new(identifier, NodeValue::SpriteNode(
+                Sprite::new(10.0, 10.0, texture_identifier.to_string()))));
+        }
+        scene
+    }
+
+    /// Returns the texture and the number of meshes of each planned batch,
+    /// sorted by texture
+    fn planned_textures(renderer: &mut GLSceneRenderer, scene: &SceneGraph)
+        -> Vec<(Option<String>, usize)> {
+        renderer.pending_meshes.clear();
+        renderer.submit_node(scene.root());
+        let mut batches: Vec<_> = plan_batches(&renderer.pending_meshes, false).iter()
+            .map(|plan| (plan.attributes.texture_identifier().clone(), plan.meshes.len()))
+            .collect();
+        batches.sort();
+        batches
+    }
+
+    #[test]
+    fn sprite_texture_changes_move_the_sprite_to_the_new_batch() {
+        let mut renderer = GLSceneRenderer::new(Rc::new(RefCell::new(EmptyStore)),
+                                                Rc::new(RefCell::new(EmptyStore)));
+
+        let scene = sprite_scene(&[("a", "red"), ("b", "blue")]);
+        assert_eq!(planned_textures(&mut renderer, &scene),
+                   vec!((Some("blue".into()), 1), (Some("red".into()), 1)));
+
+        // The node now has the texture of the first sprite
+        let scene = sprite_scene(&[("a", "red"), ("b", "red")]);
+        assert_eq!(planned_textures(&mut renderer, &scene),
+                   vec!((Some("red".into()), 2)));
+
+        // The override takes precedence over the texture of the node
+        renderer.set_sprite_texture("a", "blue");
+        assert_eq!(planned_textures(&mut renderer, &scene),
+                   vec!((Some("blue".into()), 1), (Some("red".into()), 1)));
+    }
+
+    #[test]
+    fn instanced_nodes_keep_their_own_cached_meshes() {
+        let mut renderer = GLSceneRenderer::new(Rc::new(RefCell::new(EmptyStore)),
+                                                Rc::new(RefCell::new(EmptyStore)));
+        renderer.set_node_instancing(true);
+        renderer.set_mesh_caching(true);
+
+        // Two labels sharing an identifier, under different parents
+        let mut scene = SceneGraph::new();
+        for &(parent_identifier, label_texture) in [("left", "red"), ("right", "blue")].iter() {
+            let mut parent = SceneNode::new(parent_identifier, NodeValue::SpriteNode(
+                Sprite::new(10.0, 10.0, "green".into())));
+            parent.add_child(SceneNode::new("label", NodeValue::SpriteNode(
+                Sprite::new(10.0, 10.0, label_texture.into()))));
+            scene.root_mut().add_child(parent);
+        }
+
+        let expected_batches = vec!((Some("blue".into()), 1),
+                                    (Some("green".into()), 2),
+                                    (Some("red".into()), 1));
+        assert_eq!(planned_textures(&mut renderer, &scene), expected_batches);
+        // The second frame is made of the cached meshes
+        assert_eq!(planned_textures(&mut renderer, &scene), expected_batches);
+    }
+}
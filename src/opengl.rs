@@ -24,6 +24,10 @@
 
 //! This modules contains wrappers and utilities for OpenGL 
 
+use std::borrow::Borrow;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::ffi::{CString, c_void};
 
 /// Loads OpenGL symbols through a load function
@@ -33,6 +37,222 @@ where
     gl::load_with(load_function);
 }
 
+/// Error reported by glGetError
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GLError {
+    InvalidEnum,
+    InvalidValue,
+    InvalidOperation,
+    InvalidFramebufferOperation,
+    OutOfMemory,
+    StackUnderflow,
+    StackOverflow,
+    /// Error code the bindings don't know about
+    Unknown(gl::types::GLenum)
+}
+
+impl GLError {
+    /// Returns the error corresponding to a glGetError code, None for
+    /// gl::NO_ERROR
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::opengl::GLError;
+    ///
+    /// assert_eq!(GLError::from_code(gl::NO_ERROR), None);
+    /// assert_eq!(GLError::from_code(gl::INVALID_VALUE), Some(GLError::InvalidValue));
+    /// assert_eq!(GLError::from_code(0x1234), Some(GLError::Unknown(0x1234)));
+    /// ```
+    pub fn from_code(code: gl::types::GLenum) -> Option<GLError> {
+        match code {
+            gl::NO_ERROR => None,
+            gl::INVALID_ENUM => Some(GLError::InvalidEnum),
+            gl::INVALID_VALUE => Some(GLError::InvalidValue),
+            gl::INVALID_OPERATION => Some(GLError::InvalidOperation),
+            gl::INVALID_FRAMEBUFFER_OPERATION => Some(GLError::InvalidFramebufferOperation),
+            gl::OUT_OF_MEMORY => Some(GLError::OutOfMemory),
+            gl::STACK_UNDERFLOW => Some(GLError::StackUnderflow),
+            gl::STACK_OVERFLOW => Some(GLError::StackOverflow),
+            code => Some(GLError::Unknown(code))
+        }
+    }
+}
+
+impl std::fmt::Display for GLError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GLError::InvalidEnum => write!(f, "Invalid enum"),
+            GLError::InvalidValue => write!(f, "Invalid value"),
+            GLError::InvalidOperation => write!(f, "Invalid operation"),
+            GLError::InvalidFramebufferOperation => write!(f, "Invalid framebuffer operation"),
+            GLError::OutOfMemory => write!(f, "Out of memory"),
+            GLError::StackUnderflow => write!(f, "Stack underflow"),
+            GLError::StackOverflow => write!(f, "Stack overflow"),
+            GLError::Unknown(code) => write!(f, "Unknown error {:#x}", code)
+        }
+    }
+}
+
+/// Returns the first error reported by OpenGL since the last check
+///
+/// The errors reported after it are cleared as well.
+pub fn check_error() -> Result<(), GLError> {
+    let first_error = GLError::from_code(unsafe { gl::GetError() });
+    if first_error.is_some() {
+        while unsafe { gl::GetError() } != gl::NO_ERROR {}
+    }
+
+    match first_error {
+        Some(error) => Err(error),
+        None => Ok(())
+    }
+}
+
+/// Panics if OpenGL reported an error since the last check, in debug
+/// builds only
+///
+/// The label tells where the check happened. The error may come from any
+/// call made since the previous check, including calls made outside of
+/// this crate.
+pub fn debug_check(label: &str) {
+    if cfg!(debug_assertions) {
+        if let Err(error) = check_error() {
+            panic!("OpenGL error after {}: {}", label, error);
+        }
+    }
+}
+
+/// Part of the system a debug message comes from
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DebugSource {
+    Api,
+    WindowSystem,
+    ShaderCompiler,
+    ThirdParty,
+    Application,
+    Other
+}
+
+impl DebugSource {
+    fn from_gl(source: gl::types::GLenum) -> DebugSource {
+        match source {
+            gl::DEBUG_SOURCE_API => DebugSource::Api,
+            gl::DEBUG_SOURCE_WINDOW_SYSTEM => DebugSource::WindowSystem,
+            gl::DEBUG_SOURCE_SHADER_COMPILER => DebugSource::ShaderCompiler,
+            gl::DEBUG_SOURCE_THIRD_PARTY => DebugSource::ThirdParty,
+            gl::DEBUG_SOURCE_APPLICATION => DebugSource::Application,
+            _ => DebugSource::Other
+        }
+    }
+}
+
+/// Kind of event a debug message reports
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DebugType {
+    Error,
+    DeprecatedBehavior,
+    UndefinedBehavior,
+    Portability,
+    Performance,
+    Marker,
+    PushGroup,
+    PopGroup,
+    Other
+}
+
+impl DebugType {
+    fn from_gl(message_type: gl::types::GLenum) -> DebugType {
+        match message_type {
+            gl::DEBUG_TYPE_ERROR => DebugType::Error,
+            gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => DebugType::DeprecatedBehavior,
+            gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => DebugType::UndefinedBehavior,
+            gl::DEBUG_TYPE_PORTABILITY => DebugType::Portability,
+            gl::DEBUG_TYPE_PERFORMANCE => DebugType::Performance,
+            gl::DEBUG_TYPE_MARKER => DebugType::Marker,
+            gl::DEBUG_TYPE_PUSH_GROUP => DebugType::PushGroup,
+            gl::DEBUG_TYPE_POP_GROUP => DebugType::PopGroup,
+            _ => DebugType::Other
+        }
+    }
+}
+
+/// Importance of a debug message
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DebugSeverity {
+    Notification,
+    Low,
+    Medium,
+    High
+}
+
+impl DebugSeverity {
+    fn from_gl(severity: gl::types::GLenum) -> DebugSeverity {
+        match severity {
+            gl::DEBUG_SEVERITY_HIGH => DebugSeverity::High,
+            gl::DEBUG_SEVERITY_MEDIUM => DebugSeverity::Medium,
+            gl::DEBUG_SEVERITY_LOW => DebugSeverity::Low,
+            _ => DebugSeverity::Notification
+        }
+    }
+}
+
+/// Function receiving the debug messages of the context
+pub type DebugCallback = fn(DebugSource, DebugType, gl::types::GLuint, DebugSeverity, &str);
+
+/// Sends the debug messages of the context to a callback
+///
+/// This requires OpenGL 4.3 or the GL_KHR_debug extension, nothing is
+/// done otherwise and false is returned. Messages are sent synchronously,
+/// from the thread making the faulty call. Most drivers only send them for
+/// contexts created with the debug flag.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tuber_graphics_opengl::opengl;
+///
+/// opengl::enable_debug_output(|source, message_type, id, severity, message| {
+///     eprintln!("{:?} {:?} {} {:?}: {}", source, message_type, id, severity, message);
+/// });
+/// ```
+pub fn enable_debug_output(callback: DebugCallback) -> bool {
+    let supported = (context_version() >= (4, 3) || has_extension("GL_KHR_debug"))
+        && gl::DebugMessageCallback::is_loaded();
+    if !supported {
+        return false;
+    }
+
+    unsafe {
+        gl::Enable(gl::DEBUG_OUTPUT);
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl::DebugMessageCallback(Some(forward_debug_message),
+                                 callback as *const c_void);
+    }
+
+    true
+}
+
+/// Converts a debug message and passes it to the callback given as user
+/// parameter
+extern "system" fn forward_debug_message(source: gl::types::GLenum,
+                                         message_type: gl::types::GLenum,
+                                         id: gl::types::GLuint,
+                                         severity: gl::types::GLenum,
+                                         length: gl::types::GLsizei,
+                                         message: *const gl::types::GLchar,
+                                         callback: *mut c_void) {
+    let callback: DebugCallback = unsafe { std::mem::transmute(callback) };
+    let message = unsafe {
+        std::slice::from_raw_parts(message as *const u8, length.max(0) as usize)
+    };
+    callback(DebugSource::from_gl(source),
+             DebugType::from_gl(message_type),
+             id,
+             DebugSeverity::from_gl(severity),
+             &String::from_utf8_lossy(message));
+}
+
 /// Wrapper function for glDrawArrays
 pub fn draw_arrays(mode: gl::types::GLenum,
                    first: gl::types::GLint,
@@ -45,6 +265,322 @@ pub fn draw_elements(mode: gl::types::GLenum,
                      data_type: gl::types::GLenum,
                      indices: *const gl::types::GLvoid) {
     unsafe { gl::DrawElements(mode, count, data_type, indices); }
+    debug_check("glDrawElements");
+}
+
+/// Wrapper function for glDrawElementsInstanced
+pub fn draw_elements_instanced(mode: gl::types::GLenum,
+                               count: gl::types::GLsizei,
+                               data_type: gl::types::GLenum,
+                               indices: *const gl::types::GLvoid,
+                               instance_count: gl::types::GLsizei) {
+    unsafe { gl::DrawElementsInstanced(mode, count, data_type, indices, instance_count); }
+    debug_check("glDrawElementsInstanced");
+}
+
+/// Returns the major and minor version of the OpenGL context
+pub fn context_version() -> (gl::types::GLint, gl::types::GLint) {
+    let mut major = 0;
+    let mut minor = 0;
+    unsafe {
+        gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+        gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    }
+
+    (major, minor)
+}
+
+/// Returns whether the context supports an extension, such as
+/// "GL_EXT_texture_compression_s3tc"
+pub fn has_extension(name: &str) -> bool {
+    let mut extension_count = 0;
+    unsafe { gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut extension_count); }
+
+    (0..extension_count as gl::types::GLuint).any(|index| {
+        let extension = unsafe { gl::GetStringi(gl::EXTENSIONS, index) };
+        !extension.is_null() && unsafe {
+            std::ffi::CStr::from_ptr(extension as *const std::os::raw::c_char)
+        }.to_bytes() == name.as_bytes()
+    })
+}
+
+/// Returns whether the context supports an S3TC compressed format
+pub fn supports_compressed_format(internal_format: gl::types::GLenum) -> bool {
+    match internal_format {
+        COMPRESSED_RGBA_S3TC_DXT1 |
+        COMPRESSED_RGBA_S3TC_DXT3 |
+        COMPRESSED_RGBA_S3TC_DXT5 => has_extension("GL_EXT_texture_compression_s3tc"),
+        _ => false
+    }
+}
+
+/// Returns whether the context supports anisotropic filtering, core since
+/// OpenGL 4.6
+pub fn supports_anisotropic_filtering() -> bool {
+    context_version() >= (4, 6)
+        || has_extension("GL_EXT_texture_filter_anisotropic")
+        || has_extension("GL_ARB_texture_filter_anisotropic")
+}
+
+/// Returns the largest `max_vertices` a geometry shader can declare
+///
+/// OpenGL guarantees at least 256. The total number of output components,
+/// the vertices times their outputs, is limited as well, by
+/// gl::MAX_GEOMETRY_TOTAL_OUTPUT_COMPONENTS.
+pub fn max_geometry_output_vertices() -> gl::types::GLint {
+    let mut max_vertices = 0;
+    unsafe { gl::GetIntegerv(gl::MAX_GEOMETRY_OUTPUT_VERTICES, &mut max_vertices); }
+    max_vertices
+}
+
+/// Returns whether the context supports compute shaders, which requires
+/// OpenGL 4.3
+pub fn supports_compute_shaders() -> bool {
+    context_version() >= (4, 3)
+}
+
+/// Runs the compute shader of the program in use on a grid of work groups
+pub fn dispatch_compute(x: gl::types::GLuint,
+                        y: gl::types::GLuint,
+                        z: gl::types::GLuint) -> Result<(), String> {
+    if !supports_compute_shaders() {
+        let (major, minor) = context_version();
+        return Err(format!("Compute shaders require OpenGL 4.3, the context is {}.{}",
+                           major, minor));
+    }
+
+    unsafe { gl::DispatchCompute(x, y, z); }
+    debug_check("glDispatchCompute");
+    Ok(())
+}
+
+/// Orders the memory accesses made by shaders before and after the
+/// barrier, for the kinds of accesses given by `barriers`
+/// (gl::SHADER_STORAGE_BARRIER_BIT for instance)
+pub fn memory_barrier(barriers: gl::types::GLbitfield) {
+    unsafe { gl::MemoryBarrier(barriers); }
+}
+
+/// Returns the identifier of the shader program in use
+pub fn current_program() -> gl::types::GLuint {
+    let mut identifier = 0;
+    unsafe { gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut identifier); }
+    identifier as gl::types::GLuint
+}
+
+/// Uses the shader program with the given identifier, 0 uses no program
+pub fn use_program(identifier: gl::types::GLuint) {
+    unsafe { gl::UseProgram(identifier); }
+}
+
+/// Unbinds the texture bound to a target of the active texture unit
+pub fn unbind_texture(target: gl::types::GLenum) {
+    unsafe { gl::BindTexture(target, 0); }
+}
+
+/// Sets the texture unit affected by texture bindings
+pub fn set_active_texture_unit(unit: gl::types::GLuint) {
+    unsafe { gl::ActiveTexture(gl::TEXTURE0 + unit); }
+}
+
+/// Returns the location of a uniform of the shader program in use, -1 if
+/// it has no active uniform with this name
+fn current_uniform_location(uniform: &str) -> gl::types::GLint {
+    match CString::new(uniform) {
+        Ok(uniform_string) => unsafe {
+            gl::GetUniformLocation(current_program(), uniform_string.as_ptr())
+        },
+        Err(_) => -1
+    }
+}
+
+/// Sets an integer uniform, such as a sampler, of the shader program in
+/// use
+///
+/// Uniforms that don't exist in the program are ignored, as are names
+/// containing a nul byte.
+pub fn set_uniform_int(uniform: &str, value: gl::types::GLint) {
+    let location = current_uniform_location(uniform);
+    if location != -1 {
+        unsafe { gl::Uniform1i(location, value); }
+    }
+}
+
+/// Sets a 4x4 matrix uniform of the shader program in use
+///
+/// Uniforms that don't exist in the program are ignored, as are names
+/// containing a nul byte.
+pub fn set_uniform_mat4(uniform: &str, value: &nalgebra_glm::Mat4) {
+    let location = current_uniform_location(uniform);
+    if location != -1 {
+        unsafe {
+            gl::UniformMatrix4fv(location, 1, gl::FALSE,
+                                 nalgebra_glm::value_ptr(value).as_ptr());
+        }
+    }
+}
+
+/// Enables an OpenGL capability, like gl::DEPTH_TEST
+pub fn enable(cap: gl::types::GLenum) {
+    unsafe { gl::Enable(cap); }
+}
+
+/// Disables an OpenGL capability
+pub fn disable(cap: gl::types::GLenum) {
+    unsafe { gl::Disable(cap); }
+}
+
+/// Enables or disables the depth test
+pub fn set_depth_test(enabled: bool) {
+    if enabled {
+        enable(gl::DEPTH_TEST);
+    } else {
+        disable(gl::DEPTH_TEST);
+    }
+}
+
+/// Sets the function comparing a fragment depth to the stored one,
+/// gl::LESS by default
+pub fn set_depth_func(func: gl::types::GLenum) {
+    unsafe { gl::DepthFunc(func); }
+}
+
+/// Sets whether fragments passing the depth test write their depth
+pub fn set_depth_mask(write: bool) {
+    unsafe { gl::DepthMask(if write { gl::TRUE } else { gl::FALSE }); }
+}
+
+/// Sets the winding order of front-facing polygons, gl::CCW or gl::CW
+pub fn set_front_face(mode: gl::types::GLenum) {
+    unsafe { gl::FrontFace(mode); }
+}
+
+/// Sets which faces are culled, gl::FRONT, gl::BACK or
+/// gl::FRONT_AND_BACK, None disables face culling
+pub fn set_cull_face(mode: Option<gl::types::GLenum>) {
+    unsafe {
+        match mode {
+            Some(mode) => {
+                gl::Enable(gl::CULL_FACE);
+                gl::CullFace(mode);
+            },
+            None => gl::Disable(gl::CULL_FACE)
+        }
+    }
+}
+
+/// Sets the offset added to the depth of polygons when polygon offset is
+/// enabled
+///
+/// The offset is `factor * slope + units * resolution`, negative values
+/// move the polygons toward the camera. This allows to draw coplanar
+/// geometry such as outlines and decals without z-fighting.
+pub fn set_polygon_offset(factor: f32, units: f32) {
+    unsafe { gl::PolygonOffset(factor, units); }
+}
+
+/// Enables the polygon offset for filled polygons
+pub fn enable_polygon_offset_fill() {
+    unsafe { gl::Enable(gl::POLYGON_OFFSET_FILL); }
+}
+
+/// Disables the polygon offset for filled polygons
+pub fn disable_polygon_offset_fill() {
+    unsafe { gl::Disable(gl::POLYGON_OFFSET_FILL); }
+}
+
+/// Sets the row alignment of the pixel data read by texture uploads
+pub fn set_unpack_alignment(alignment: gl::types::GLint) {
+    unsafe { gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment); }
+}
+
+/// Returns the row alignment of the pixel data read by texture uploads
+pub fn unpack_alignment() -> gl::types::GLint {
+    let mut alignment = 0;
+    unsafe { gl::GetIntegerv(gl::UNPACK_ALIGNMENT, &mut alignment); }
+    alignment
+}
+
+/// Returns the size in bytes of a pixel of the given format and type, or
+/// None if the combination isn't supported
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::opengl::pixel_size;
+///
+/// assert_eq!(pixel_size(gl::RGBA, gl::UNSIGNED_BYTE), Some(4));
+/// assert_eq!(pixel_size(gl::RG, gl::FLOAT), Some(8));
+/// assert_eq!(pixel_size(gl::RGB, gl::UNSIGNED_SHORT_5_6_5), Some(2));
+/// assert_eq!(pixel_size(gl::RGB, gl::UNSIGNED_INT_8_8_8_8), None);
+/// ```
+pub fn pixel_size(format: gl::types::GLenum,
+                  data_type: gl::types::GLenum) -> Option<usize> {
+    let channels = match format {
+        gl::RED | gl::RED_INTEGER | gl::DEPTH_COMPONENT | gl::STENCIL_INDEX => 1,
+        gl::RG | gl::RG_INTEGER | gl::DEPTH_STENCIL => 2,
+        gl::RGB | gl::BGR | gl::RGB_INTEGER | gl::BGR_INTEGER => 3,
+        gl::RGBA | gl::BGRA | gl::RGBA_INTEGER | gl::BGRA_INTEGER => 4,
+        _ => return None
+    };
+
+    // Packed types hold every channel of the pixel
+    match (data_type, channels) {
+        (gl::UNSIGNED_BYTE, _) | (gl::BYTE, _) => Some(channels),
+        (gl::UNSIGNED_SHORT, _) | (gl::SHORT, _) | (gl::HALF_FLOAT, _) => Some(channels * 2),
+        (gl::UNSIGNED_INT, _) | (gl::INT, _) | (gl::FLOAT, _) => Some(channels * 4),
+        (gl::UNSIGNED_BYTE_3_3_2, 3) | (gl::UNSIGNED_BYTE_2_3_3_REV, 3) => Some(1),
+        (gl::UNSIGNED_SHORT_5_6_5, 3) | (gl::UNSIGNED_SHORT_5_6_5_REV, 3)
+            | (gl::UNSIGNED_SHORT_4_4_4_4, 4) | (gl::UNSIGNED_SHORT_4_4_4_4_REV, 4)
+            | (gl::UNSIGNED_SHORT_5_5_5_1, 4) | (gl::UNSIGNED_SHORT_1_5_5_5_REV, 4) => Some(2),
+        (gl::UNSIGNED_INT_8_8_8_8, 4) | (gl::UNSIGNED_INT_8_8_8_8_REV, 4)
+            | (gl::UNSIGNED_INT_10_10_10_2, 4) | (gl::UNSIGNED_INT_2_10_10_10_REV, 4)
+            | (gl::UNSIGNED_INT_24_8, 2) => Some(4),
+        _ => None
+    }
+}
+
+/// Returns the number of levels of a complete mipmap chain for an image
+/// of the given size, the base level included
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::opengl::mipmap_level_count;
+///
+/// assert_eq!(mipmap_level_count(1, 1), 1);
+/// assert_eq!(mipmap_level_count(8, 4), 4);
+/// assert_eq!(mipmap_level_count(5, 3), 3);
+/// assert_eq!(mipmap_level_count(0, 0), 1);
+/// ```
+pub fn mipmap_level_count(width: usize, height: usize) -> usize {
+    let mut size = width.max(height);
+    let mut level_count = 1;
+    while size > 1 {
+        size >>= 1;
+        level_count += 1;
+    }
+    level_count
+}
+
+/// Restricts drawing to a rectangle of the framebuffer, given from its
+/// bottom-left corner, None draws everywhere
+pub fn set_scissor(rectangle: Option<(gl::types::GLint, gl::types::GLint,
+                                      gl::types::GLsizei, gl::types::GLsizei)>) {
+    unsafe {
+        match rectangle {
+            Some((x, y, width, height)) => {
+                gl::Enable(gl::SCISSOR_TEST);
+                gl::Scissor(x, y, width, height);
+            },
+            None => gl::Disable(gl::SCISSOR_TEST)
+        }
+    }
+}
+
+/// Binds a framebuffer for drawing and reading, 0 binds the default one
+pub fn bind_framebuffer(identifier: gl::types::GLuint) {
+    unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, identifier); }
 }
 
 /// Sets the viewport
@@ -62,13 +598,352 @@ pub fn clear(mask: gl::types::GLenum) {
     unsafe { gl::Clear(mask); }
 }
 
+/// Reads the pixels of a rectangle of the framebuffer as RGBA bytes
+///
+/// The rows are ordered bottom to top, starting at (x, y).
+pub fn read_pixels(x: gl::types::GLint, y: gl::types::GLint,
+                   width: gl::types::GLsizei,
+                   height: gl::types::GLsizei) -> Vec<u8> {
+    let mut pixels = vec![0u8; width.max(0) as usize * height.max(0) as usize * 4];
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(x, y, width, height,
+                       gl::RGBA, gl::UNSIGNED_BYTE,
+                       pixels.as_mut_ptr() as *mut gl::types::GLvoid);
+    }
+
+    pixels
+}
+
+/// Sets up blending for glyph atlases with straight alpha
+///
+/// The glyph coverage is the alpha of the texture, its color comes from
+/// the vertex color: `SRC_ALPHA, ONE_MINUS_SRC_ALPHA` with an additive
+/// equation, for both the color and the alpha channels.
 pub fn enable_font_blending() {
     unsafe {
         gl::Enable(gl::BLEND);
+        gl::BlendEquation(gl::FUNC_ADD);
         gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
     }
 }
 
+/// Blending state of the context
+///
+/// # Examples
+///
+/// ```no_run
+/// use tuber_graphics_opengl::opengl;
+///
+/// let blend_state = opengl::BlendState::current();
+/// opengl::enable_font_blending();
+/// // Draw text
+/// blend_state.restore();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BlendState {
+    enabled: bool,
+    source_rgb: gl::types::GLenum,
+    destination_rgb: gl::types::GLenum,
+    source_alpha: gl::types::GLenum,
+    destination_alpha: gl::types::GLenum,
+    equation_rgb: gl::types::GLenum,
+    equation_alpha: gl::types::GLenum
+}
+
+impl BlendState {
+    /// Returns the blending state currently set
+    pub fn current() -> BlendState {
+        let get = |parameter: gl::types::GLenum| {
+            let mut value = 0;
+            unsafe { gl::GetIntegerv(parameter, &mut value); }
+            value as gl::types::GLenum
+        };
+
+        BlendState {
+            enabled: unsafe { gl::IsEnabled(gl::BLEND) } == gl::TRUE,
+            source_rgb: get(gl::BLEND_SRC_RGB),
+            destination_rgb: get(gl::BLEND_DST_RGB),
+            source_alpha: get(gl::BLEND_SRC_ALPHA),
+            destination_alpha: get(gl::BLEND_DST_ALPHA),
+            equation_rgb: get(gl::BLEND_EQUATION_RGB),
+            equation_alpha: get(gl::BLEND_EQUATION_ALPHA)
+        }
+    }
+
+    /// Sets the blending state back to this one
+    pub fn restore(&self) {
+        unsafe {
+            if self.enabled {
+                gl::Enable(gl::BLEND);
+            } else {
+                gl::Disable(gl::BLEND);
+            }
+
+            gl::BlendFuncSeparate(self.source_rgb, self.destination_rgb,
+                                  self.source_alpha, self.destination_alpha);
+            gl::BlendEquationSeparate(self.equation_rgb, self.equation_alpha);
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// Snapshot of the OpenGL state modified by the renderer
+///
+/// It holds the bound program, vertex array object and array buffer, the
+/// active texture unit, the 2D textures bound to some units, the blending
+/// state, the front face and the scissor test.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tuber_graphics_opengl::opengl;
+///
+/// let state = opengl::GLState::current(&[0]);
+/// // Draw with the renderer
+/// state.restore();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct GLState {
+    program: gl::types::GLuint,
+    vertex_array: gl::types::GLuint,
+    array_buffer: gl::types::GLuint,
+    active_texture: gl::types::GLenum,
+    textures: Vec<(gl::types::GLuint, gl::types::GLuint)>,
+    blend_state: BlendState,
+    front_face: gl::types::GLenum,
+    scissor_test: bool,
+    scissor_box: [gl::types::GLint; 4]
+}
+
+impl GLState {
+    /// Returns the state currently set, with the 2D textures bound to the
+    /// given texture units
+    pub fn current(texture_units: &[gl::types::GLuint]) -> GLState {
+        let get = |parameter: gl::types::GLenum| {
+            let mut value = 0;
+            unsafe { gl::GetIntegerv(parameter, &mut value); }
+            value as gl::types::GLuint
+        };
+
+        let active_texture = get(gl::ACTIVE_TEXTURE);
+        let textures = texture_units.iter()
+            .map(|&unit| {
+                unsafe { gl::ActiveTexture(gl::TEXTURE0 + unit); }
+                (unit, get(gl::TEXTURE_BINDING_2D))
+            })
+            .collect();
+        unsafe { gl::ActiveTexture(active_texture); }
+
+        let mut scissor_box = [0; 4];
+        unsafe { gl::GetIntegerv(gl::SCISSOR_BOX, scissor_box.as_mut_ptr()); }
+
+        GLState {
+            program: get(gl::CURRENT_PROGRAM),
+            vertex_array: get(gl::VERTEX_ARRAY_BINDING),
+            array_buffer: get(gl::ARRAY_BUFFER_BINDING),
+            active_texture,
+            textures,
+            blend_state: BlendState::current(),
+            front_face: get(gl::FRONT_FACE),
+            scissor_test: unsafe { gl::IsEnabled(gl::SCISSOR_TEST) } == gl::TRUE,
+            scissor_box
+        }
+    }
+
+    /// Sets the state back to this one
+    pub fn restore(&self) {
+        unsafe {
+            gl::UseProgram(self.program);
+            gl::BindVertexArray(self.vertex_array);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.array_buffer);
+            for &(unit, texture) in self.textures.iter() {
+                gl::ActiveTexture(gl::TEXTURE0 + unit);
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+            }
+            gl::ActiveTexture(self.active_texture);
+            gl::FrontFace(self.front_face);
+            if self.scissor_test {
+                gl::Enable(gl::SCISSOR_TEST);
+            } else {
+                gl::Disable(gl::SCISSOR_TEST);
+            }
+            gl::Scissor(self.scissor_box[0], self.scissor_box[1],
+                        self.scissor_box[2], self.scissor_box[3]);
+        }
+        self.blend_state.restore();
+    }
+}
+
+/// Enables blending, with the factors set by `set_blend_func`
+pub fn enable_blending() {
+    unsafe { gl::Enable(gl::BLEND); }
+}
+
+/// Disables blending
+pub fn disable_blending() {
+    unsafe { gl::Disable(gl::BLEND); }
+}
+
+/// Sets the source and destination blending factors
+pub fn set_blend_func(source: gl::types::GLenum,
+                      destination: gl::types::GLenum) {
+    unsafe { gl::BlendFunc(source, destination); }
+}
+
+/// Sets the equation used to combine the source and destination colors
+///
+/// The factors set by `set_blend_func` are ignored by gl::MIN and gl::MAX
+pub fn set_blend_equation(mode: gl::types::GLenum) {
+    unsafe { gl::BlendEquation(mode); }
+}
+
+/// Sets whether lines are anti-aliased
+///
+/// Smoothing computes the coverage of the line edges into the alpha of
+/// the fragments, it has no visible effect unless alpha blending is
+/// enabled.
+pub fn set_line_smooth(smooth: bool) {
+    unsafe {
+        if smooth {
+            gl::Enable(gl::LINE_SMOOTH);
+            gl::Hint(gl::LINE_SMOOTH_HINT, gl::NICEST);
+        } else {
+            gl::Disable(gl::LINE_SMOOTH);
+        }
+    }
+}
+
+/// Sets whether polygon edges are anti-aliased
+///
+/// As for lines, alpha blending must be enabled for this to have an
+/// effect. Adjacent triangles may show seams along their shared edges.
+pub fn set_polygon_smooth(smooth: bool) {
+    unsafe {
+        if smooth {
+            gl::Enable(gl::POLYGON_SMOOTH);
+            gl::Hint(gl::POLYGON_SMOOTH_HINT, gl::NICEST);
+        } else {
+            gl::Disable(gl::POLYGON_SMOOTH);
+        }
+    }
+}
+
+/// Blending presets
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BlendMode {
+    /// Standard alpha blending, for textures with straight alpha
+    Alpha,
+    /// Alpha blending for textures with premultiplied alpha, such as the
+    /// ones uploaded with `TextureParameters::premultiply`
+    PremultipliedAlpha,
+    /// Adds the source to the destination
+    Additive,
+    /// Multiplies the destination by the source
+    Multiply,
+    /// Inverse of the product of the inverted colors, lightens the destination
+    Screen,
+    /// Keeps the minimum of the source and destination
+    Darken,
+    /// Keeps the maximum of the source and destination
+    Lighten,
+    /// Subtracts the source from the destination
+    Subtract,
+    /// Adds the source and destination weighted by the given source and
+    /// destination factors
+    Custom(gl::types::GLenum, gl::types::GLenum)
+}
+
+impl BlendMode {
+    /// Returns the source and destination blending factors of the preset
+    pub fn factors(&self) -> (gl::types::GLenum, gl::types::GLenum) {
+        match self {
+            BlendMode::Alpha => (gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA),
+            BlendMode::PremultipliedAlpha => (gl::ONE, gl::ONE_MINUS_SRC_ALPHA),
+            BlendMode::Additive => (gl::SRC_ALPHA, gl::ONE),
+            BlendMode::Multiply => (gl::DST_COLOR, gl::ZERO),
+            BlendMode::Screen => (gl::ONE, gl::ONE_MINUS_SRC_COLOR),
+            BlendMode::Darken | BlendMode::Lighten => (gl::ONE, gl::ONE),
+            BlendMode::Subtract => (gl::SRC_ALPHA, gl::ONE),
+            BlendMode::Custom(source, destination) => (*source, *destination)
+        }
+    }
+
+    /// Returns the blending equation of the preset
+    pub fn equation(&self) -> gl::types::GLenum {
+        match self {
+            BlendMode::Darken => gl::MIN,
+            BlendMode::Lighten => gl::MAX,
+            BlendMode::Subtract => gl::FUNC_REVERSE_SUBTRACT,
+            _ => gl::FUNC_ADD
+        }
+    }
+
+    /// Enables blending and sets the factors and equation of the preset
+    pub fn apply(&self) {
+        let (source, destination) = self.factors();
+        enable_blending();
+        set_blend_func(source, destination);
+        set_blend_equation(self.equation());
+    }
+}
+
+/// OpenGL object that can be bound to the context
+///
+/// Objects stay bound until they are explicitly unbound, any GL call
+/// issued in between can modify them. Prefer scoping bindings with
+/// [`BindGuard`](struct.BindGuard.html) when the object must not be left
+/// bound.
+pub trait Bindable {
+    fn bind(&self);
+    fn unbind(&self);
+}
+
+/// Guard keeping an object bound until it is dropped
+pub struct BindGuard<'a, T: Bindable> {
+    object: &'a T
+}
+
+impl<'a, T: Bindable> BindGuard<'a, T> {
+    /// Binds the object and returns a guard that unbinds it on drop
+    pub fn new(object: &'a T) -> BindGuard<'a, T> {
+        object.bind();
+        BindGuard { object }
+    }
+}
+
+impl<'a, T: Bindable> Drop for BindGuard<'a, T> {
+    fn drop(&mut self) {
+        self.object.unbind();
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Plain data that can be read back from the bytes of a buffer
+///
+/// Every bit pattern is a valid value of these types. The trait is sealed,
+/// it is only implemented for integers, floats and arrays of them.
+pub trait BufferData: Copy + sealed::Sealed {}
+
+macro_rules! impl_buffer_data {
+    ($($data_type:ty),*) => {
+        $(
+            impl sealed::Sealed for $data_type {}
+            impl BufferData for $data_type {}
+        )*
+    }
+}
+
+impl_buffer_data!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+impl<T: BufferData, const N: usize> sealed::Sealed for [T; N] {}
+impl<T: BufferData, const N: usize> BufferData for [T; N] {}
+
 /// OpenGL buffer object wrapper
 pub struct BufferObject {
     identifier: gl::types::GLuint,
@@ -98,30 +973,96 @@ impl BufferObject {
         buffer
     }
 
+    pub fn identifier(&self) -> gl::types::GLuint {
+        self.identifier
+    }
+
+    pub fn target(&self) -> gl::types::GLenum {
+        self.target
+    }
+
     /// Binds the buffer to its target
     pub fn bind(&self) {
         unsafe { gl::BindBuffer(self.target, self.identifier); }
     }
 
+    /// Binds the buffer to an indexed binding point of its target, such as
+    /// the binding points of gl::UNIFORM_BUFFER or gl::SHADER_STORAGE_BUFFER
+    pub fn bind_base(&self, binding_point: gl::types::GLuint) {
+        unsafe { gl::BindBufferBase(self.target, binding_point, self.identifier); }
+    }
+
     /// Unbinds the buffer from its target
     pub fn unbind(&self) {
         unsafe { gl::BindBuffer(self.target, 0); }
     }
 
+    /// Binds the buffer until the returned guard is dropped
+    pub fn bound(&self) -> BindGuard<BufferObject> {
+        BindGuard::new(self)
+    }
+
     pub fn unmap(&self) {
         unsafe { gl::UnmapBuffer(self.target); }
     }
 
+    /// Returns the size of the buffer's data store in bytes
+    ///
+    /// The buffer is bound to its target meanwhile, and unbound afterwards.
+    pub fn size(&self) -> usize {
+        let mut size = 0;
+        self.bind();
+        unsafe { gl::GetBufferParameteriv(self.target, gl::BUFFER_SIZE, &mut size); }
+        self.unbind();
+        size as usize
+    }
+
     pub fn map_buffer_range(&self,
                             offset: usize,
                             length: usize,
                             access: gl::types::GLbitfield) -> *mut gl::types::GLvoid {
-        unsafe { 
+        let pointer = unsafe {
             gl::MapBufferRange(self.target,
                                offset as gl::types::GLintptr,
                                length as gl::types::GLsizeiptr,
                                access)
+        };
+        debug_check("glMapBufferRange");
+        pointer
+    }
+
+    /// Reads back the first values of the buffer through a mapped range
+    ///
+    /// The buffer is bound to its target meanwhile, and unbound afterwards.
+    /// Values written by shaders, such as the ones of a shader storage
+    /// buffer, must be made visible first with `memory_barrier` and
+    /// gl::BUFFER_UPDATE_BARRIER_BIT.
+    ///
+    /// Fails if the buffer is smaller than the values or can't be mapped.
+    pub fn read_to_vec<T: BufferData>(&self, count: usize) -> Result<Vec<T>, String> {
+        if count == 0 {
+            // Empty ranges can't be mapped
+            return Ok(vec!());
+        }
+
+        let size = count * std::mem::size_of::<T>();
+        let buffer_size = self.size();
+        if size > buffer_size {
+            return Err(format!("Can't read {} bytes from a buffer of {} bytes",
+                               size, buffer_size));
         }
+
+        self.bind();
+        let pointer = self.map_buffer_range(0, size, gl::MAP_READ_BIT) as *const T;
+        if pointer.is_null() {
+            self.unbind();
+            return Err("Couldn't map the buffer".into());
+        }
+
+        let values = unsafe { std::slice::from_raw_parts(pointer, count).to_vec() };
+        self.unmap();
+        self.unbind();
+        Ok(values)
     }
 
     pub fn map_buffer(&self, access: gl::types::GLenum) -> *mut gl::types::GLvoid {
@@ -141,8 +1082,7 @@ impl BufferObject {
                                 data,
                                 usage);
         }
-
-        // TODO error handling
+        debug_check("glBufferData");
     }
 
     pub fn update_data(&self,
@@ -158,6 +1098,35 @@ impl BufferObject {
 
         // TODO error handling
     }
+
+    /// Copies the first bytes of the buffer to the start of another one
+    ///
+    /// The copy goes through the gl::COPY_READ_BUFFER and
+    /// gl::COPY_WRITE_BUFFER targets, leaving the other bindings untouched.
+    pub fn copy_data_to(&self, destination: &BufferObject, size: usize) {
+        unsafe {
+            gl::BindBuffer(gl::COPY_READ_BUFFER, self.identifier);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, destination.identifier);
+            gl::CopyBufferSubData(gl::COPY_READ_BUFFER,
+                                  gl::COPY_WRITE_BUFFER,
+                                  0,
+                                  0,
+                                  size as gl::types::GLsizeiptr);
+            gl::BindBuffer(gl::COPY_READ_BUFFER, 0);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+        }
+        debug_check("glCopyBufferSubData");
+    }
+}
+
+impl Bindable for BufferObject {
+    fn bind(&self) {
+        BufferObject::bind(self);
+    }
+
+    fn unbind(&self) {
+        BufferObject::unbind(self);
+    }
 }
 
 impl Drop for BufferObject {
@@ -166,6 +1135,78 @@ impl Drop for BufferObject {
     }
 }
 
+/// Buffer of uniform block values shared by several shader programs
+///
+/// The buffer is bound to a binding point, and the uniform blocks of the
+/// programs are tied to the same binding point with
+/// `ShaderProgram::bind_uniform_block`. The data must follow the layout of
+/// the block, usually `std140`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tuber_graphics_opengl::opengl;
+///
+/// # fn camera(shader_program: &opengl::ShaderProgram) -> Result<(), String> {
+/// // layout (std140) uniform Camera { mat4 view; mat4 projection; };
+/// let camera = opengl::UniformBuffer::new(2 * std::mem::size_of::<nalgebra_glm::Mat4>());
+/// camera.update(&[nalgebra_glm::Mat4::identity(),
+///                 nalgebra_glm::ortho(0.0, 800.0, 600.0, 0.0, 0.0, 100.0)]);
+/// camera.bind_base(0);
+/// shader_program.bind_uniform_block("Camera", 0)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct UniformBuffer {
+    buffer: BufferObject,
+    size: usize
+}
+
+impl UniformBuffer {
+    /// Creates a uniform buffer of the given size in bytes
+    pub fn new(size: usize) -> UniformBuffer {
+        UniformBuffer {
+            buffer: BufferObject::with_size(gl::UNIFORM_BUFFER, size),
+            size
+        }
+    }
+
+    pub fn identifier(&self) -> gl::types::GLuint {
+        self.buffer.identifier()
+    }
+
+    /// Returns the size of the buffer in bytes
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Binds the buffer to a uniform block binding point
+    pub fn bind_base(&self, binding_point: gl::types::GLuint) {
+        self.buffer.bind_base(binding_point);
+    }
+
+    /// Replaces the start of the buffer with the given values
+    ///
+    /// Panics if the values don't fit in the buffer.
+    pub fn update<T: Copy>(&self, data: &[T]) {
+        self.update_range(0, data);
+    }
+
+    /// Replaces the values at an offset in bytes into the buffer
+    ///
+    /// Panics if the values don't fit in the buffer.
+    pub fn update_range<T: Copy>(&self, offset: usize, data: &[T]) {
+        let size = data.len() * std::mem::size_of::<T>();
+        assert!(offset + size <= self.size,
+                "{} bytes at offset {} don't fit in a uniform buffer of {} bytes",
+                size, offset, self.size);
+
+        self.buffer.bind();
+        self.buffer.update_data(offset, size, data.as_ptr() as *const gl::types::GLvoid);
+        self.buffer.unbind();
+    }
+}
+
 /// OpenGL vertex array object wrapper
 pub struct VertexArrayObject {
     identifier: gl::types::GLuint,
@@ -192,7 +1233,12 @@ impl VertexArrayObject {
         unsafe { gl::BindVertexArray(0); }
     }
 
-    /// Enables and sets an attribute of the vertex array object
+    /// Binds the vertex array object until the returned guard is dropped
+    pub fn bound(&self) -> BindGuard<VertexArrayObject> {
+        BindGuard::new(self)
+    }
+
+    /// Enables and sets an attribute of the vertex array object
     pub fn set_attribute(&self,
                          index: usize,
                          size: usize,
@@ -210,6 +1256,44 @@ impl VertexArrayObject {
                                     pointer);
         }
     }
+
+    /// Sets how often an attribute advances, the vertex array object must
+    /// be bound
+    ///
+    /// A divisor of 0 advances it every vertex, a divisor of N every N
+    /// instances.
+    pub fn set_attribute_divisor(&self, index: usize, divisor: gl::types::GLuint) {
+        unsafe { gl::VertexAttribDivisor(index as gl::types::GLuint, divisor); }
+    }
+
+    /// Enables and sets all the attributes described by a vertex layout
+    ///
+    /// The stride and the offset of each attribute are computed from the
+    /// layout, the attributes are assigned to consecutive indices
+    /// starting at 0.
+    pub fn configure(&self, layout: &VertexLayout) {
+        let stride = layout.stride();
+        let mut offset = 0;
+        for (index, attribute) in layout.attributes().iter().enumerate() {
+            self.set_attribute(index,
+                               attribute.size(),
+                               attribute.kind(),
+                               attribute.normalized(),
+                               stride,
+                               offset as *const gl::types::GLvoid);
+            offset += attribute.byte_size();
+        }
+    }
+}
+
+impl Bindable for VertexArrayObject {
+    fn bind(&self) {
+        VertexArrayObject::bind(self);
+    }
+
+    fn unbind(&self) {
+        VertexArrayObject::unbind(self);
+    }
 }
 
 impl Drop for VertexArrayObject {
@@ -218,18 +1302,335 @@ impl Drop for VertexArrayObject {
     }
 }
 
+/// OpenGL framebuffer object wrapper, to render into textures
+///
+/// # Examples
+///
+/// ```no_run
+/// use tuber_graphics_opengl::opengl;
+///
+/// let color_texture = opengl::Texture::from_image(
+///     &opengl::DecodedImage::new(800, 600, gl::RGBA, vec![0; 800 * 600 * 4]),
+///     &opengl::TextureParameters::new());
+/// let framebuffer = opengl::Framebuffer::new();
+/// framebuffer.bind();
+/// framebuffer.attach_color_texture(&color_texture, 0);
+/// framebuffer.check_complete()?;
+/// // Render the scene, then sample color_texture
+/// framebuffer.unbind();
+/// # Ok::<(), String>(())
+/// ```
+pub struct Framebuffer {
+    identifier: gl::types::GLuint
+}
+
+impl Framebuffer {
+    /// Creates a new framebuffer object
+    pub fn new() -> Framebuffer {
+        let mut identifier = 0;
+        unsafe { gl::GenFramebuffers(1, &mut identifier); }
+
+        Framebuffer {
+            identifier
+        }
+    }
+
+    pub fn identifier(&self) -> gl::types::GLuint {
+        self.identifier
+    }
+
+    /// Binds the framebuffer for drawing and reading
+    pub fn bind(&self) {
+        bind_framebuffer(self.identifier);
+    }
+
+    /// Binds the default framebuffer
+    pub fn unbind(&self) {
+        bind_framebuffer(0);
+    }
+
+    /// Attaches the base level of a 2D texture to a color attachment point,
+    /// the framebuffer must be bound
+    pub fn attach_color_texture(&self, texture: &Texture,
+                                attachment_index: gl::types::GLuint) {
+        unsafe {
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER,
+                                     gl::COLOR_ATTACHMENT0 + attachment_index,
+                                     texture.target(),
+                                     texture.identifier(),
+                                     0);
+        }
+    }
+
+    /// Attaches a renderbuffer to the depth attachment point, or to the
+    /// depth and stencil one for gl::DEPTH24_STENCIL8 and
+    /// gl::DEPTH32F_STENCIL8 storages, the framebuffer must be bound
+    pub fn attach_depth_renderbuffer(&self, renderbuffer: &Renderbuffer) {
+        let attachment = match renderbuffer.internal_format() {
+            gl::DEPTH24_STENCIL8 | gl::DEPTH32F_STENCIL8 => gl::DEPTH_STENCIL_ATTACHMENT,
+            _ => gl::DEPTH_ATTACHMENT
+        };
+
+        unsafe {
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER,
+                                        attachment,
+                                        gl::RENDERBUFFER,
+                                        renderbuffer.identifier());
+        }
+    }
+
+    /// Returns an error describing why the framebuffer can't be drawn
+    /// into, the framebuffer must be bound
+    pub fn check_complete(&self) -> Result<(), String> {
+        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+        let reason = match status {
+            gl::FRAMEBUFFER_COMPLETE => return Ok(()),
+            gl::FRAMEBUFFER_UNDEFINED => "undefined",
+            gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => "incomplete attachment",
+            gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => "missing attachment",
+            gl::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => "incomplete draw buffer",
+            gl::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => "incomplete read buffer",
+            gl::FRAMEBUFFER_UNSUPPORTED => "unsupported combination of formats",
+            gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => "incomplete multisample",
+            gl::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => "incomplete layer targets",
+            _ => "unknown status"
+        };
+
+        Err(format!("Framebuffer {} is incomplete: {} ({:#x})",
+                    self.identifier, reason, status))
+    }
+}
+
+impl Default for Framebuffer {
+    fn default() -> Framebuffer {
+        Framebuffer::new()
+    }
+}
+
+impl Bindable for Framebuffer {
+    fn bind(&self) {
+        Framebuffer::bind(self);
+    }
+
+    fn unbind(&self) {
+        Framebuffer::unbind(self);
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteFramebuffers(1, &self.identifier); }
+    }
+}
+
+/// OpenGL renderbuffer object wrapper, for framebuffer attachments that
+/// aren't sampled, like depth and stencil buffers
+///
+/// # Examples
+///
+/// ```no_run
+/// use tuber_graphics_opengl::opengl;
+///
+/// let depth_stencil_buffer = opengl::Renderbuffer::new();
+/// depth_stencil_buffer.bind();
+/// depth_stencil_buffer.storage(gl::DEPTH24_STENCIL8, 800, 600);
+///
+/// let framebuffer = opengl::Framebuffer::new();
+/// framebuffer.bind();
+/// framebuffer.attach_depth_renderbuffer(&depth_stencil_buffer);
+/// ```
+pub struct Renderbuffer {
+    identifier: gl::types::GLuint,
+    internal_format: Cell<gl::types::GLenum>
+}
+
+impl Renderbuffer {
+    /// Creates a new renderbuffer object, without storage
+    pub fn new() -> Renderbuffer {
+        let mut identifier = 0;
+        unsafe { gl::GenRenderbuffers(1, &mut identifier); }
+
+        Renderbuffer {
+            identifier,
+            internal_format: Cell::new(gl::NONE)
+        }
+    }
+
+    pub fn identifier(&self) -> gl::types::GLuint {
+        self.identifier
+    }
+
+    /// Returns the format of the storage, gl::NONE until it is allocated
+    pub fn internal_format(&self) -> gl::types::GLenum {
+        self.internal_format.get()
+    }
+
+    /// Binds the renderbuffer
+    pub fn bind(&self) {
+        unsafe { gl::BindRenderbuffer(gl::RENDERBUFFER, self.identifier); }
+    }
+
+    /// Unbinds the renderbuffer
+    pub fn unbind(&self) {
+        unsafe { gl::BindRenderbuffer(gl::RENDERBUFFER, 0); }
+    }
+
+    /// Allocates the storage of the renderbuffer, which must be bound
+    pub fn storage(&self, internal_format: gl::types::GLenum,
+                   width: gl::types::GLsizei, height: gl::types::GLsizei) {
+        unsafe { gl::RenderbufferStorage(gl::RENDERBUFFER, internal_format, width, height); }
+        self.internal_format.set(internal_format);
+    }
+}
+
+impl Default for Renderbuffer {
+    fn default() -> Renderbuffer {
+        Renderbuffer::new()
+    }
+}
+
+impl Bindable for Renderbuffer {
+    fn bind(&self) {
+        Renderbuffer::bind(self);
+    }
+
+    fn unbind(&self) {
+        Renderbuffer::unbind(self);
+    }
+}
+
+impl Drop for Renderbuffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteRenderbuffers(1, &self.identifier); }
+    }
+}
+
+/// Describes the interleaved attributes of a vertex, in order
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::opengl::VertexLayout;
+///
+/// let layout = VertexLayout::new()
+///     .attribute(3, gl::FLOAT, gl::FALSE)
+///     .attribute(2, gl::FLOAT, gl::FALSE);
+/// assert_eq!(layout.attributes().len(), 2);
+/// assert_eq!(layout.stride(), 5 * std::mem::size_of::<f32>());
+/// ```
+pub struct VertexLayout {
+    attributes: Vec<VertexAttribute>
+}
+
+impl VertexLayout {
+    /// Creates an empty vertex layout
+    pub fn new() -> VertexLayout {
+        VertexLayout {
+            attributes: vec!()
+        }
+    }
+
+    /// Appends an attribute made of `size` components of the given kind
+    pub fn attribute(mut self,
+                     size: usize,
+                     kind: gl::types::GLenum,
+                     normalized: gl::types::GLboolean) -> VertexLayout {
+        self.attributes.push(VertexAttribute {
+            size,
+            kind,
+            normalized
+        });
+        self
+    }
+
+    pub fn attributes(&self) -> &Vec<VertexAttribute> {
+        &self.attributes
+    }
+
+    /// Returns the size in bytes of a whole vertex
+    pub fn stride(&self) -> usize {
+        self.attributes.iter()
+            .map(|attribute| attribute.byte_size())
+            .sum()
+    }
+}
+
+impl Default for VertexLayout {
+    fn default() -> VertexLayout {
+        VertexLayout::new()
+    }
+}
+
+/// Attribute of a vertex layout
+pub struct VertexAttribute {
+    size: usize,
+    kind: gl::types::GLenum,
+    normalized: gl::types::GLboolean
+}
+
+impl VertexAttribute {
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn kind(&self) -> gl::types::GLenum {
+        self.kind
+    }
+
+    pub fn normalized(&self) -> gl::types::GLboolean {
+        self.normalized
+    }
+
+    /// Returns the size in bytes of the attribute
+    pub fn byte_size(&self) -> usize {
+        let component_size = match self.kind {
+            gl::BYTE | gl::UNSIGNED_BYTE => 1,
+            gl::SHORT | gl::UNSIGNED_SHORT | gl::HALF_FLOAT => 2,
+            gl::INT | gl::UNSIGNED_INT | gl::FLOAT => 4,
+            gl::DOUBLE => 8,
+            _ => panic!("Unsupported vertex attribute type")
+        };
+
+        self.size * component_size
+    }
+}
+
 /// OpenGL shader program wrapper
+///
+/// The locations of the uniforms are looked up once and cached. The
+/// sources of the shaders are kept to tell why a uniform has no location.
+/// This is the program type stored by `shader::ShaderStore`, which
+/// re-exports it.
 pub struct ShaderProgram {
-    identifier: gl::types::GLuint
+    identifier: gl::types::GLuint,
+    uniform_locations: RefCell<HashMap<String, gl::types::GLint>>,
+    source_code: String
 }
 
 impl ShaderProgram {
     /// Creates a shader program from a slice of shaders
-    pub fn from_shaders(shaders: &[Shader]) -> Result<ShaderProgram, String> {
+    ///
+    /// A gl::GEOMETRY_SHADER can be given between the vertex and the
+    /// fragment shaders, for instance to expand points into quads. Its
+    /// `max_vertices` must not exceed `max_geometry_output_vertices`, and
+    /// large outputs slow it down, so it should emit as few vertices as
+    /// possible. A lone gl::COMPUTE_SHADER makes a compute program, to be
+    /// run with `dispatch_compute`. Shared shaders, like the ones of a
+    /// `ShaderCache`, can be given as well.
+    pub fn from_shaders<S>(shaders: &[S]) -> Result<ShaderProgram, String>
+        where S: Borrow<Shader> {
         let identifier = unsafe { gl::CreateProgram() };
+        // Owned right away so that the program is deleted if linking fails
+        let mut shader_program = ShaderProgram {
+            identifier,
+            uniform_locations: RefCell::new(HashMap::new()),
+            source_code: String::new()
+        };
 
         for shader in shaders {
+            let shader: &Shader = shader.borrow();
             unsafe { gl::AttachShader(identifier, shader.identifier()); }
+            shader_program.source_code += &shader.source_code();
         }
 
         unsafe { gl::LinkProgram(identifier); }
@@ -260,10 +1661,72 @@ impl ShaderProgram {
         }
 
         for shader in shaders {
+            let shader: &Shader = shader.borrow();
             unsafe { gl::DetachShader(identifier, shader.identifier()); }
         }
 
-        Ok(ShaderProgram { identifier })
+        Ok(shader_program)
+    }
+
+    /// Creates a shader program from shader source files, given with the
+    /// kind of their shader
+    pub fn from_files<P>(paths: &[(P, gl::types::GLenum)]) -> Result<ShaderProgram, String>
+        where P: AsRef<std::path::Path> {
+        let mut shaders = vec!();
+        for (path, kind) in paths {
+            shaders.push(Shader::from_file(path.as_ref(), *kind)?);
+        }
+
+        ShaderProgram::from_shaders(&shaders)
+    }
+
+    /// Recompiles the program from shader source files, such as the ones
+    /// it was created from, after they changed
+    ///
+    /// The program is only replaced once the new one links, otherwise the
+    /// error is returned and the program is left as it was. If the program
+    /// was in use, the new one is used instead. The cached uniform
+    /// locations are looked up again in the new program.
+    pub fn reload_from_files<P>(&mut self, paths: &[(P, gl::types::GLenum)]) -> Result<(), String>
+        where P: AsRef<std::path::Path> {
+        let mut reloaded_program = ShaderProgram::from_files(paths)?;
+        let in_use = current_program() == self.identifier;
+
+        // The previous program is deleted along with reloaded_program
+        std::mem::swap(&mut self.identifier, &mut reloaded_program.identifier);
+        std::mem::swap(&mut self.source_code, &mut reloaded_program.source_code);
+        if in_use {
+            self.use_program();
+        }
+
+        let uniforms: Vec<String> = self.uniform_locations.borrow_mut()
+            .drain()
+            .map(|(uniform, _)| uniform)
+            .collect();
+        for uniform in uniforms {
+            self.uniform_location(&uniform);
+        }
+
+        Ok(())
+    }
+
+    /// Creates a shader program from a file containing both a vertex and a
+    /// fragment shader
+    ///
+    /// See `split_shader_sections` for the format of the file.
+    pub fn from_combined_file(path: &std::path::Path) -> Result<ShaderProgram, String> {
+        let source_code = std::fs::read_to_string(path)
+            .map_err(|e| format!("Couldn't read shader source file {}: {}",
+                                 path.display(), e))?;
+        let (vertex_source, fragment_source) = split_shader_sections(&source_code)?;
+
+        let vertex_shader = Shader::from_source(&vertex_source, gl::VERTEX_SHADER)?;
+        let fragment_shader = Shader::from_source(&fragment_source, gl::FRAGMENT_SHADER)?;
+        ShaderProgram::from_shaders(&[vertex_shader, fragment_shader])
+    }
+
+    pub fn identifier(&self) -> gl::types::GLuint {
+        self.identifier
     }
 
     /// Uses the shader program
@@ -271,35 +1734,420 @@ impl ShaderProgram {
         unsafe { gl::UseProgram(self.identifier); }
     }
 
+    /// Ties a uniform block of the program to a binding point, which a
+    /// `UniformBuffer` can be bound to
+    ///
+    /// An error is returned if the program has no active block with this
+    /// name.
+    pub fn bind_uniform_block(&self, name: &str,
+                              binding_point: gl::types::GLuint) -> Result<(), String> {
+        let block_name = CString::new(name)
+            .map_err(|_| format!("Invalid uniform block name {}", name))?;
+        let block_index = unsafe {
+            gl::GetUniformBlockIndex(self.identifier, block_name.as_ptr())
+        };
+        if block_index == gl::INVALID_INDEX {
+            return Err(format!("Uniform block {} not found", name));
+        }
+
+        unsafe { gl::UniformBlockBinding(self.identifier, block_index, binding_point); }
+        Ok(())
+    }
+
+    /// Returns the name, type and array size of each active uniform of the
+    /// program
+    ///
+    /// Uniforms the compiler removed because they aren't used aren't
+    /// active. The names of arrays end with `[0]`.
+    pub fn active_uniforms(&self) -> Vec<(String, gl::types::GLenum, gl::types::GLint)> {
+        let mut uniform_count = 0;
+        let mut max_name_length = 0;
+        unsafe {
+            gl::GetProgramiv(self.identifier, gl::ACTIVE_UNIFORMS, &mut uniform_count);
+            gl::GetProgramiv(self.identifier, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_name_length);
+        }
+
+        let mut name_buffer = vec![0u8; max_name_length.max(1) as usize];
+        (0..uniform_count as gl::types::GLuint).map(|index| {
+            let mut name_length = 0;
+            let mut size = 0;
+            let mut kind = 0;
+            unsafe {
+                gl::GetActiveUniform(self.identifier,
+                                     index,
+                                     name_buffer.len() as gl::types::GLsizei,
+                                     &mut name_length,
+                                     &mut size,
+                                     &mut kind,
+                                     name_buffer.as_mut_ptr() as *mut gl::types::GLchar);
+            }
+
+            let name = String::from_utf8_lossy(&name_buffer[..name_length as usize]).into_owned();
+            (name, kind, size)
+        }).collect()
+    }
+
+    /// Returns the location of a uniform, -1 if the program has no active
+    /// uniform with this name
+    pub fn uniform_location(&self, uniform: &str) -> gl::types::GLint {
+        if let Some(&location) = self.uniform_locations.borrow().get(uniform) {
+            return location;
+        }
+
+        let location = match CString::new(uniform) {
+            Ok(uniform_string) => unsafe {
+                gl::GetUniformLocation(self.identifier, uniform_string.as_ptr())
+            },
+            Err(_) => -1
+        };
+        self.uniform_locations.borrow_mut().insert(uniform.into(), location);
+        location
+    }
+
+    /// Sets a uniform of the program, which must be in use
+    ///
+    /// Uniforms the program doesn't have are ignored.
+    pub fn set_uniform_value<V: UniformValue>(&self, uniform: &str, value: V) {
+        let location = self.uniform_location(uniform);
+        if location != -1 {
+            value.set_uniform(location);
+        }
+    }
+
+    /// Sets a uniform of the program, which must be in use
+    ///
+    /// Unlike `set_uniform_value`, an error is returned if the program has
+    /// no such uniform. It tells whether the uniform is declared in the
+    /// shaders but was optimized out because it isn't used, or isn't
+    /// declared at all, for instance because its name is misspelled.
+    pub fn try_set_uniform_value<V: UniformValue>(&self, uniform: &str, value: V)
+        -> Result<(), String> {
+        let location = self.uniform_location(uniform);
+        if location != -1 {
+            value.set_uniform(location);
+            return Ok(());
+        }
+
+        // Only the variable name of array elements or struct members
+        let variable = uniform.split(|c| c == '[' || c == '.').next().unwrap_or(uniform);
+        if declares_uniform(&self.source_code, variable) {
+            Err(format!("Uniform {} is inactive, it was optimized out because it isn't used",
+                        uniform))
+        } else {
+            Err(format!("Uniform {} isn't declared in the shaders", uniform))
+        }
+    }
+
+    /// Sets a mat4 uniform of the program, which must be in use
+    ///
+    /// The matrix is uploaded in column-major order. Uniforms the program
+    /// doesn't have are ignored.
     pub fn set_uniform_mat4(&mut self, uniform: &str, uniform_value: nalgebra_glm::Mat4) {
+        self.set_uniform_value(uniform, uniform_value);
+    }
+
+    /// Sets a mat4 uniform of the program, which must be in use, from a
+    /// matrix stored in row-major order
+    ///
+    /// The matrix is transposed by OpenGL while uploading. Uniforms the
+    /// program doesn't have are ignored.
+    pub fn set_uniform_mat4_transposed(&self, uniform: &str, uniform_value: [f32; 16]) {
+        self.set_uniform_value(uniform, RowMajorMat4(uniform_value));
+    }
+}
+
+/// Value that can be uploaded to a uniform of the matching GLSL type
+pub trait UniformValue {
+    /// Uploads the value to the uniform at the given location of the
+    /// program in use
+    fn set_uniform(&self, location: gl::types::GLint);
+}
+
+impl UniformValue for gl::types::GLint {
+    fn set_uniform(&self, location: gl::types::GLint) {
+        unsafe { gl::Uniform1i(location, *self); }
+    }
+}
+
+impl UniformValue for gl::types::GLuint {
+    fn set_uniform(&self, location: gl::types::GLint) {
+        unsafe { gl::Uniform1ui(location, *self); }
+    }
+}
+
+impl UniformValue for gl::types::GLfloat {
+    fn set_uniform(&self, location: gl::types::GLint) {
+        unsafe { gl::Uniform1f(location, *self); }
+    }
+}
+
+impl UniformValue for nalgebra_glm::Vec2 {
+    fn set_uniform(&self, location: gl::types::GLint) {
+        unsafe { gl::Uniform2f(location, self.x, self.y); }
+    }
+}
+
+impl UniformValue for nalgebra_glm::Vec3 {
+    fn set_uniform(&self, location: gl::types::GLint) {
+        unsafe { gl::Uniform3f(location, self.x, self.y, self.z); }
+    }
+}
+
+impl UniformValue for nalgebra_glm::Vec4 {
+    fn set_uniform(&self, location: gl::types::GLint) {
+        unsafe { gl::Uniform4f(location, self.x, self.y, self.z, self.w); }
+    }
+}
+
+impl UniformValue for nalgebra_glm::Mat4 {
+    fn set_uniform(&self, location: gl::types::GLint) {
         unsafe {
-            let uniform_string = CString::new(uniform).unwrap();
-            let location = gl::GetUniformLocation(self.identifier, uniform_string.as_ptr());
-            gl::UniformMatrix4fv(location, 1, gl::FALSE, (&nalgebra_glm::value_ptr(&uniform_value)).as_ptr());
+            gl::UniformMatrix4fv(location, 1, gl::FALSE, nalgebra_glm::value_ptr(self).as_ptr());
         }
     }
 }
 
+/// 4x4 matrix stored in row-major order, as laid out by math libraries
+/// like cgmath or mint
+///
+/// OpenGL transposes it when uploading, so it doesn't have to be
+/// transposed beforehand.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RowMajorMat4(pub [f32; 16]);
+
+impl UniformValue for RowMajorMat4 {
+    fn set_uniform(&self, location: gl::types::GLint) {
+        unsafe {
+            gl::UniformMatrix4fv(location, 1, gl::TRUE, self.0.as_ptr());
+        }
+    }
+}
+
+impl<'a, V: UniformValue> UniformValue for &'a V {
+    fn set_uniform(&self, location: gl::types::GLint) {
+        (*self).set_uniform(location);
+    }
+}
+
+impl Drop for ShaderProgram {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteProgram(self.identifier); }
+    }
+}
+
+/// Splits the source of a combined shader into the sources of its vertex
+/// and fragment stages
+///
+/// Each stage starts after a line containing only `#vertex` or
+/// `#fragment`. The lines before the first marker, like the `#version`
+/// directive, are put at the start of both stages. An error is returned if
+/// a stage is missing or appears twice.
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::opengl::split_shader_sections;
+///
+/// let source = "#version 330 core\n#vertex\nvoid main() {}\n#fragment\nout vec4 c;\n";
+/// let (vertex, fragment) = split_shader_sections(source).unwrap();
+/// assert_eq!(vertex, "#version 330 core\nvoid main() {}\n");
+/// assert_eq!(fragment, "#version 330 core\nout vec4 c;\n");
+///
+/// assert!(split_shader_sections("#vertex\nvoid main() {}\n").is_err());
+/// ```
+pub fn split_shader_sections(source_code: &str) -> Result<(String, String), String> {
+    let mut preamble = String::new();
+    let mut vertex_source: Option<String> = None;
+    let mut fragment_source: Option<String> = None;
+    let mut current_section = None;
+
+    for line in source_code.lines() {
+        let marker = match line.trim() {
+            "#vertex" => Some(gl::VERTEX_SHADER),
+            "#fragment" => Some(gl::FRAGMENT_SHADER),
+            _ => None
+        };
+
+        if let Some(kind) = marker {
+            let section = if kind == gl::VERTEX_SHADER {
+                &mut vertex_source
+            } else {
+                &mut fragment_source
+            };
+
+            if section.is_some() {
+                return Err(format!("Duplicate {} section in combined shader", line.trim()));
+            }
+
+            *section = Some(preamble.clone());
+            current_section = Some(kind);
+            continue;
+        }
+
+        let destination = match current_section {
+            Some(gl::VERTEX_SHADER) => vertex_source.as_mut().unwrap(),
+            Some(_) => fragment_source.as_mut().unwrap(),
+            None => &mut preamble
+        };
+        destination.push_str(line);
+        destination.push('\n');
+    }
+
+    match (vertex_source, fragment_source) {
+        (Some(vertex_source), Some(fragment_source)) => Ok((vertex_source, fragment_source)),
+        (None, _) => Err("Missing #vertex section in combined shader".into()),
+        (_, None) => Err("Missing #fragment section in combined shader".into())
+    }
+}
+
+/// Checks whether GLSL source code declares a uniform variable with the
+/// given name
+///
+/// Comments are skipped, and so are the members of uniform blocks.
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::opengl::declares_uniform;
+///
+/// let source_code = "layout (location = 0) in vec3 Position;
+///                    uniform highp vec2 offset, scales[2];
+///                    // uniform float intensity;
+///                    void main() { gl_Position = vec4(Position, 1.0); }";
+/// assert!(declares_uniform(source_code, "offset"));
+/// assert!(declares_uniform(source_code, "scales"));
+/// assert!(!declares_uniform(source_code, "Position"));
+/// assert!(!declares_uniform(source_code, "intensity"));
+/// assert!(!declares_uniform(source_code, "vec2"));
+/// ```
+pub fn declares_uniform(source_code: &str, name: &str) -> bool {
+    const PRECISION_QUALIFIERS: [&str; 3] = ["lowp", "mediump", "highp"];
+
+    let source_code = strip_comments(source_code);
+    let tokens = glsl_tokens(&source_code);
+    let mut index = 0;
+    while index < tokens.len() {
+        if tokens[index] != "uniform" {
+            index += 1;
+            continue;
+        }
+
+        index += 1;
+        while index < tokens.len() && PRECISION_QUALIFIERS.contains(&tokens[index]) {
+            index += 1;
+        }
+        // Skips the type
+        index += 1;
+        if tokens.get(index) == Some(&"{") {
+            // Uniform block
+            continue;
+        }
+
+        // The names are separated by commas, after the type or the array
+        // size of the type
+        let mut expects_name = true;
+        let mut depth = 0;
+        while index < tokens.len() && tokens[index] != ";" {
+            match tokens[index] {
+                "[" | "(" => depth += 1,
+                "]" | ")" => depth -= 1,
+                "," if depth == 0 => expects_name = true,
+                token if depth == 0 && expects_name => {
+                    if token == name {
+                        return true;
+                    }
+                    expects_name = false;
+                },
+                _ => {}
+            }
+            index += 1;
+        }
+    }
+
+    false
+}
+
+/// Replaces the comments of GLSL source code by spaces
+fn strip_comments(source_code: &str) -> String {
+    let mut stripped = String::with_capacity(source_code.len());
+    let mut characters = source_code.chars().peekable();
+    while let Some(character) = characters.next() {
+        match (character, characters.peek()) {
+            ('/', Some('/')) => {
+                while characters.peek().map_or(false, |&c| c != '\n') {
+                    characters.next();
+                }
+                stripped.push(' ');
+            },
+            ('/', Some('*')) => {
+                characters.next();
+                let mut previous = ' ';
+                while let Some(c) = characters.next() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                stripped.push(' ');
+            },
+            _ => stripped.push(character)
+        }
+    }
+    stripped
+}
+
+/// Splits GLSL source code into words, such as identifiers, and single
+/// punctuation characters
+fn glsl_tokens(source_code: &str) -> Vec<&str> {
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut tokens = vec!();
+    let mut word_start = None;
+    for (index, character) in source_code.char_indices() {
+        if is_word_char(character) {
+            word_start = word_start.or(Some(index));
+            continue;
+        }
+
+        if let Some(start) = word_start.take() {
+            tokens.push(&source_code[start..index]);
+        }
+        if !character.is_whitespace() {
+            tokens.push(&source_code[index..index + character.len_utf8()]);
+        }
+    }
+    if let Some(start) = word_start {
+        tokens.push(&source_code[start..]);
+    }
+    tokens
+}
+
 /// OpenGL shader object wrapper
 pub struct Shader {
     identifier: gl::types::GLuint
 }
 
 impl Shader {
+    /// Creates a shader from a source file
+    ///
+    /// An error is returned if the file can't be read, or with the
+    /// compilation log if the shader doesn't compile.
     pub fn from_file(path: &std::path::Path,
                      kind: gl::types::GLenum) -> Result<Shader, String> {
-        let source_code = Shader::read_source_file(&path);
+        let source_code = Shader::read_source_file(&path)?;
         Shader::from_source(&source_code, kind)
     }
 
     /// Creates a shader from source code
     pub fn from_source(source_code: &str,
                        kind: gl::types::GLenum) -> Result<Shader, String>{
-        let identifier = unsafe { gl::CreateShader(kind) };
         let source_string = CString::new(source_code)
-            .expect("Interior nul byte found");
-        
-        Shader::compile(identifier, source_string)?;
+            .map_err(|_| "Interior nul byte found in shader source".to_string())?;
+        let identifier = unsafe { gl::CreateShader(kind) };
+
+        if let Err(error) = Shader::compile(identifier, source_string) {
+            unsafe { gl::DeleteShader(identifier); }
+            return Err(error);
+        }
 
         Ok(Shader { identifier })
     }
@@ -308,18 +2156,30 @@ impl Shader {
         self.identifier
     }
 
-    /// Reads a shader source file into a string
-    fn read_source_file(path: &std::path::Path) -> String {
-        use std::fs::File;
-        use std::io::Read;
+    /// Returns the source code the shader was compiled from
+    pub fn source_code(&self) -> String {
+        let mut length = 0;
+        unsafe {
+            gl::GetShaderiv(self.identifier, gl::SHADER_SOURCE_LENGTH, &mut length);
+        }
 
-        let mut source_file = File::open(path)
-            .expect("Couldn't open shader source file");
-        let mut content = String::new();
-        source_file.read_to_string(&mut content)
-            .expect("Couldn't read shader source file");
+        let mut buffer = vec![0u8; length.max(1) as usize];
+        let mut written_length = 0;
+        unsafe {
+            gl::GetShaderSource(self.identifier,
+                                buffer.len() as gl::types::GLsizei,
+                                &mut written_length,
+                                buffer.as_mut_ptr() as *mut gl::types::GLchar);
+        }
 
-        content
+        String::from_utf8_lossy(&buffer[..written_length as usize]).into_owned()
+    }
+
+    /// Reads a shader source file into a string
+    fn read_source_file(path: &std::path::Path) -> Result<String, String> {
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Couldn't read shader source file {}: {}",
+                                 path.display(), e))
     }
 
     /// Compiles a shader
@@ -362,10 +2222,84 @@ impl Shader {
     }
 }
 
+/// Cache of compiled shaders, keyed by a hash of their source code
+///
+/// Programs sharing a stage can link the same shader object, which is only
+/// compiled the first time its source is seen.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tuber_graphics_opengl::opengl::{ShaderCache, ShaderProgram};
+///
+/// let mut shader_cache = ShaderCache::new();
+/// let vertex_shader = shader_cache.get_or_compile("...", gl::VERTEX_SHADER)?;
+/// let red_shader = shader_cache.get_or_compile("...", gl::FRAGMENT_SHADER)?;
+/// let red_program = ShaderProgram::from_shaders(&[vertex_shader.clone(), red_shader])?;
+/// # Ok::<(), String>(())
+/// ```
+pub struct ShaderCache {
+    shaders: HashMap<u64, Rc<Shader>>
+}
+
+impl ShaderCache {
+    pub fn new() -> ShaderCache {
+        ShaderCache {
+            shaders: HashMap::new()
+        }
+    }
+
+    /// Returns the shader compiled from the given source code, compiling
+    /// it if it isn't in the cache yet
+    ///
+    /// Compilation errors aren't cached.
+    pub fn get_or_compile(&mut self, source_code: &str,
+                          kind: gl::types::GLenum) -> Result<Rc<Shader>, String> {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        kind.hash(&mut hasher);
+        source_code.hash(&mut hasher);
+        let key = hasher.finish();
+
+        if let Some(shader) = self.shaders.get(&key) {
+            return Ok(shader.clone());
+        }
+
+        let shader = Rc::new(Shader::from_source(source_code, kind)?);
+        self.shaders.insert(key, shader.clone());
+        Ok(shader)
+    }
+
+    /// Returns the number of shaders in the cache
+    pub fn len(&self) -> usize {
+        self.shaders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shaders.is_empty()
+    }
+
+    /// Removes all the shaders from the cache
+    ///
+    /// Shaders still referenced elsewhere stay alive.
+    pub fn clear(&mut self) {
+        self.shaders.clear();
+    }
+}
+
+impl Default for ShaderCache {
+    fn default() -> ShaderCache {
+        ShaderCache::new()
+    }
+}
+
 /// OpenGL texture wrapper
 pub struct Texture {
     identifier: gl::types::GLuint,
-    target: gl::types::GLenum
+    target: gl::types::GLenum,
+    dimensions: Cell<(gl::types::GLsizei, gl::types::GLsizei)>,
+    format: Cell<gl::types::GLenum>
 }
 
 impl Texture {
@@ -376,10 +2310,160 @@ impl Texture {
 
         Texture {
             identifier,
-            target
+            target,
+            dimensions: Cell::new((0, 0)),
+            format: Cell::new(gl::NONE)
         }
     }
 
+    pub fn identifier(&self) -> gl::types::GLuint {
+        self.identifier
+    }
+
+    pub fn target(&self) -> gl::types::GLenum {
+        self.target
+    }
+
+    /// Binds the texture to the given texture unit, which is left active
+    pub fn active_bind(&self, unit: gl::types::GLuint) {
+        set_active_texture_unit(unit);
+        self.bind();
+    }
+
+    /// Creates a 2D texture from a decoded image
+    ///
+    /// This must be called on the thread owning the OpenGL context. The
+    /// rows of the image are tightly packed, whatever their width and the
+    /// format, such as single-channel gl::RED images. The texture is left
+    /// bound.
+    pub fn from_image(image: &DecodedImage,
+                      parameters: &TextureParameters) -> Texture {
+        if parameters.premultiplies() && image.format() == gl::RGBA {
+            let mut premultiplied_image = DecodedImage::new(image.width(),
+                                                            image.height(),
+                                                            image.format(),
+                                                            image.pixels().to_vec());
+            premultiplied_image.premultiply_alpha();
+            return Texture::from_image(&premultiplied_image,
+                                       &parameters.premultiply(false));
+        }
+
+        let texture = Texture::new(gl::TEXTURE_2D);
+        texture.bind();
+        set_unpack_alignment(1);
+        texture.set_2d_image_data(0,
+                                  image.format() as gl::types::GLint,
+                                  image.width(),
+                                  image.height(),
+                                  0,
+                                  image.format(),
+                                  gl::UNSIGNED_BYTE,
+                                  image.pixels().as_ptr() as *const gl::types::GLvoid);
+        set_unpack_alignment(4);
+        parameters.apply(&texture);
+        texture
+    }
+
+    /// Creates a 2D single-channel texture, with one byte per pixel
+    ///
+    /// The texture is stored as gl::RED, a quarter of the memory of an RGBA
+    /// texture, which suits glyph coverage and masks. Shaders read the
+    /// value from the red channel: to use it as alpha, read `.r` in the
+    /// shader or swizzle it with `set_swizzle([gl::ONE, gl::ONE, gl::ONE,
+    /// gl::RED])`. Rows are tightly packed, whatever their width. The
+    /// texture is left bound, with the default parameters.
+    pub fn from_grayscale(width: gl::types::GLsizei, height: gl::types::GLsizei,
+                          data: &[u8]) -> Texture {
+        assert_eq!(data.len(), (width * height) as usize,
+                   "The grayscale data doesn't match the texture size");
+
+        let image = DecodedImage::new(width, height, gl::RED, data.to_vec());
+        Texture::from_image(&image, &TextureParameters::new())
+    }
+
+    /// Creates a 2D texture from a compressed image
+    ///
+    /// An error is returned if the context doesn't support the format of
+    /// the image, or if it has more levels than a complete mipmap chain of
+    /// its size. If the image has a mipmap chain, it is used instead of
+    /// generating mipmaps, and the levels are limited to the chain.
+    pub fn from_compressed_image(image: &CompressedImage,
+                                 parameters: &TextureParameters)
+        -> Result<Texture, String> {
+        if !supports_compressed_format(image.internal_format()) {
+            return Err(format!("Unsupported compressed texture format {:#x}",
+                               image.internal_format()));
+        }
+
+        let max_level_count = mipmap_level_count(image.width().max(0) as usize,
+                                                 image.height().max(0) as usize);
+        if image.levels().len() > max_level_count {
+            return Err(format!("A {}x{} compressed image can't have {} levels",
+                               image.width(), image.height(), image.levels().len()));
+        }
+
+        let texture = Texture::new(gl::TEXTURE_2D);
+        texture.bind();
+        for (level, data) in image.levels().iter().enumerate() {
+            texture.set_compressed_2d_image_data(level as gl::types::GLint,
+                                                 image.internal_format(),
+                                                 (image.width() >> level).max(1),
+                                                 (image.height() >> level).max(1),
+                                                 data);
+        }
+
+        let level_count = image.levels().len() as gl::types::GLint;
+        if level_count > 1 {
+            TextureParameters {
+                min_filter: Some(parameters.effective_min_filter()),
+                mipmaps: false,
+                level_range: parameters.level_range.or(Some((0, level_count - 1))),
+                ..*parameters
+            }.apply(&texture);
+        } else {
+            parameters.apply(&texture);
+        }
+
+        Ok(texture)
+    }
+
+    /// Sets the compressed image data of a level of a 2D texture
+    pub fn set_compressed_2d_image_data(&self,
+                                        level: gl::types::GLint,
+                                        internal_format: gl::types::GLenum,
+                                        width: gl::types::GLsizei,
+                                        height: gl::types::GLsizei,
+                                        data: &[u8]) {
+        unsafe {
+            gl::CompressedTexImage2D(self.target,
+                                     level,
+                                     internal_format,
+                                     width,
+                                     height,
+                                     0,
+                                     data.len() as gl::types::GLsizei,
+                                     data.as_ptr() as *const gl::types::GLvoid);
+        }
+
+        if level == 0 {
+            self.dimensions.set((width, height));
+        }
+    }
+
+    /// Returns the width and height of the texture's base level
+    ///
+    /// Both are 0 until image data has been set
+    pub fn dimensions(&self) -> (gl::types::GLsizei, gl::types::GLsizei) {
+        self.dimensions.get()
+    }
+
+    /// Returns the pixel format of the texture's base level data
+    ///
+    /// gl::NONE until image data has been set
+    pub fn format(&self) -> gl::types::GLenum {
+        self.format.get()
+    }
+
     /// Sets the image data for a 2D texture
     pub fn set_2d_image_data(&self, 
                              level: gl::types::GLint,
@@ -401,6 +2485,73 @@ impl Texture {
                            data_type,
                            data);
         }
+
+        if level == 0 {
+            self.dimensions.set((width, height));
+            self.format.set(format);
+        }
+    }
+
+    /// Replaces a rectangle of the image data of a 2D texture
+    pub fn set_2d_sub_image_data(&self,
+                                 level: gl::types::GLint,
+                                 x_offset: gl::types::GLint,
+                                 y_offset: gl::types::GLint,
+                                 width: gl::types::GLsizei,
+                                 height: gl::types::GLsizei,
+                                 format: gl::types::GLenum,
+                                 data_type: gl::types::GLenum,
+                                 data: *const gl::types::GLvoid) {
+        unsafe {
+            gl::TexSubImage2D(self.target,
+                              level,
+                              x_offset,
+                              y_offset,
+                              width,
+                              height,
+                              format,
+                              data_type,
+                              data);
+        }
+    }
+
+    /// Replaces a rectangle of the image data of a 2D texture with bytes,
+    /// keeping its storage
+    ///
+    /// Unlike `set_2d_image_data`, nothing is reallocated, which suits
+    /// textures changing every frame such as video frames. The rectangle
+    /// must lie within the level, and the texture must be bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is shorter than the rectangle, with rows padded to
+    /// the unpack alignment.
+    pub fn update_sub_image(&self,
+                            level: gl::types::GLint,
+                            x_offset: gl::types::GLint,
+                            y_offset: gl::types::GLint,
+                            width: gl::types::GLsizei,
+                            height: gl::types::GLsizei,
+                            format: gl::types::GLenum,
+                            data_type: gl::types::GLenum,
+                            data: &[u8]) {
+        let pixel_size = pixel_size(format, data_type)
+            .unwrap_or_else(|| panic!("Unsupported pixel format {:#x} and type {:#x}",
+                                      format, data_type));
+        if width > 0 && height > 0 {
+            let alignment = unpack_alignment() as usize;
+            let row_size = width as usize * pixel_size;
+            let padded_row_size = (row_size + alignment - 1) / alignment * alignment;
+            let size = padded_row_size * (height as usize - 1) + row_size;
+            assert!(data.len() >= size,
+                    "The data holds {} bytes, {} are needed for a {}x{} rectangle",
+                    data.len(), size, width, height);
+        }
+
+        self.set_2d_sub_image_data(level, x_offset, y_offset, width, height,
+                                   format, data_type,
+                                   data.as_ptr() as *const gl::types::GLvoid);
+        debug_check("glTexSubImage2D");
     }
 
     /// Generates the texture mipmaps
@@ -418,6 +2569,60 @@ impl Texture {
         }
     }
 
+    /// Sets a float parameter, such as gl::TEXTURE_MIN_LOD
+    pub fn set_float_parameter(&self,
+                               parameter_name: gl::types::GLenum,
+                               parameter_value: gl::types::GLfloat) {
+        unsafe {
+            gl::TexParameterf(self.target, parameter_name, parameter_value);
+        }
+    }
+
+    /// Sets a parameter made of several floats, such as
+    /// gl::TEXTURE_BORDER_COLOR
+    pub fn set_float_array_parameter(&self,
+                                     parameter_name: gl::types::GLenum,
+                                     parameter_values: &[gl::types::GLfloat]) {
+        unsafe {
+            gl::TexParameterfv(self.target, parameter_name, parameter_values.as_ptr());
+        }
+    }
+
+    /// Sets the anisotropy of the filtering of the texture, which must be
+    /// bound
+    ///
+    /// The level is clamped between 1 and the largest anisotropy supported
+    /// by the context, and returned. Anisotropic filtering sharpens
+    /// textures seen at oblique angles, it is meant for mipmapped textures.
+    /// An error is returned if the context doesn't support it.
+    pub fn set_anisotropy(&self, level: gl::types::GLfloat)
+        -> Result<gl::types::GLfloat, String> {
+        if !supports_anisotropic_filtering() {
+            return Err("Anisotropic filtering isn't supported".into());
+        }
+
+        let mut max_level = 1.0;
+        unsafe { gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max_level); }
+        let level = level.max(1.0).min(max_level);
+        self.set_float_parameter(TEXTURE_MAX_ANISOTROPY, level);
+        Ok(level)
+    }
+
+    /// Sets the swizzle mask of the texture
+    ///
+    /// Each component is the source of the corresponding channel seen by
+    /// the shaders, e.g. `[gl::ONE, gl::ONE, gl::ONE, gl::RED]` presents a
+    /// single-channel coverage texture as white with the coverage as alpha.
+    pub fn set_swizzle(&self, mask: [gl::types::GLenum; 4]) {
+        let mask = [mask[0] as gl::types::GLint,
+                    mask[1] as gl::types::GLint,
+                    mask[2] as gl::types::GLint,
+                    mask[3] as gl::types::GLint];
+        unsafe {
+            gl::TexParameteriv(self.target, gl::TEXTURE_SWIZZLE_RGBA, mask.as_ptr());
+        }
+    }
+
     /// Binds the texture
     pub fn bind(&self) {
         unsafe { gl::BindTexture(self.target, self.identifier); }
@@ -427,4 +2632,540 @@ impl Texture {
     pub fn unbind(&self) {
         unsafe { gl::BindTexture(self.target, 0); }
     }
+
+    /// Binds the texture until the returned guard is dropped
+    pub fn bound(&self) -> BindGuard<Texture> {
+        BindGuard::new(self)
+    }
+}
+
+impl Bindable for Texture {
+    fn bind(&self) {
+        Texture::bind(self);
+    }
+
+    fn unbind(&self) {
+        Texture::unbind(self);
+    }
+}
+
+/// OpenGL 2D array texture wrapper, holding layers of RGBA images of the
+/// same size
+///
+/// All the frames of an animation can live in one texture, shaders sample
+/// it with a `sampler2DArray` and select the layer with the third texture
+/// coordinate. The array dereferences to its gl::TEXTURE_2D_ARRAY
+/// texture, for binding and setting parameters.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tuber_graphics_opengl::opengl;
+///
+/// let frames = opengl::TextureArray::new(32, 32, 4);
+/// frames.bind();
+/// for layer in 0..4 {
+///     frames.set_layer_data(layer, &[255u8; 32 * 32 * 4]);
+/// }
+/// ```
+pub struct TextureArray {
+    texture: Texture,
+    layers: gl::types::GLsizei
+}
+
+impl TextureArray {
+    /// Creates an array texture and allocates its layers, leaving it bound
+    ///
+    /// The layers are sampled with nearest filtering and clamped to their
+    /// edges.
+    pub fn new(width: gl::types::GLsizei, height: gl::types::GLsizei,
+               layers: gl::types::GLsizei) -> TextureArray {
+        let texture = Texture::new(gl::TEXTURE_2D_ARRAY);
+        texture.bind();
+        unsafe {
+            gl::TexImage3D(gl::TEXTURE_2D_ARRAY,
+                           0,
+                           gl::RGBA8 as gl::types::GLint,
+                           width,
+                           height,
+                           layers,
+                           0,
+                           gl::RGBA,
+                           gl::UNSIGNED_BYTE,
+                           std::ptr::null());
+        }
+        debug_check("glTexImage3D");
+        texture.dimensions.set((width, height));
+        texture.format.set(gl::RGBA);
+        texture.set_int_parameter(gl::TEXTURE_MIN_FILTER, gl::NEAREST as gl::types::GLint);
+        texture.set_int_parameter(gl::TEXTURE_MAG_FILTER, gl::NEAREST as gl::types::GLint);
+        texture.set_int_parameter(gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as gl::types::GLint);
+        texture.set_int_parameter(gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as gl::types::GLint);
+
+        TextureArray {
+            texture,
+            layers
+        }
+    }
+
+    pub fn layer_count(&self) -> gl::types::GLsizei {
+        self.layers
+    }
+
+    /// Sets the RGBA pixels of a layer, the texture must be bound
+    ///
+    /// The data holds the rows of the layer, 4 bytes per pixel.
+    pub fn set_layer_data(&self, layer: gl::types::GLsizei, data: &[u8]) {
+        let (width, height) = self.texture.dimensions();
+        assert!(layer < self.layers, "Layer {} is out of range", layer);
+        assert_eq!(data.len(), (width * height * 4) as usize,
+                   "The layer data doesn't match the layer size");
+
+        unsafe {
+            gl::TexSubImage3D(gl::TEXTURE_2D_ARRAY,
+                              0,
+                              0,
+                              0,
+                              layer,
+                              width,
+                              height,
+                              1,
+                              gl::RGBA,
+                              gl::UNSIGNED_BYTE,
+                              data.as_ptr() as *const gl::types::GLvoid);
+        }
+        debug_check("glTexSubImage3D");
+    }
+}
+
+impl std::ops::Deref for TextureArray {
+    type Target = Texture;
+
+    fn deref(&self) -> &Texture {
+        &self.texture
+    }
+}
+
+impl Drop for TextureArray {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteTextures(1, &self.texture.identifier()); }
+    }
+}
+
+/// OpenGL cubemap texture wrapper, for skyboxes
+///
+/// Shaders sample it with a `samplerCube` and a direction. The cubemap
+/// dereferences to its gl::TEXTURE_CUBE_MAP texture, for binding and
+/// setting parameters.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tuber_graphics_opengl::opengl;
+///
+/// let skybox = opengl::Cubemap::new();
+/// skybox.bind();
+/// for face in opengl::Cubemap::FACES.iter() {
+///     skybox.set_face_data(*face, 1, 1, &[32, 32, 64, 255]);
+/// }
+/// ```
+pub struct Cubemap {
+    texture: Texture
+}
+
+impl Cubemap {
+    /// Targets of the six faces, in the order of their values
+    pub const FACES: [gl::types::GLenum; 6] = [
+        gl::TEXTURE_CUBE_MAP_POSITIVE_X,
+        gl::TEXTURE_CUBE_MAP_NEGATIVE_X,
+        gl::TEXTURE_CUBE_MAP_POSITIVE_Y,
+        gl::TEXTURE_CUBE_MAP_NEGATIVE_Y,
+        gl::TEXTURE_CUBE_MAP_POSITIVE_Z,
+        gl::TEXTURE_CUBE_MAP_NEGATIVE_Z
+    ];
+
+    /// Creates a cubemap, leaving it bound
+    ///
+    /// The faces are sampled with linear filtering, and the three
+    /// coordinates are clamped to the edges so that no seam shows between
+    /// faces.
+    pub fn new() -> Cubemap {
+        let texture = Texture::new(gl::TEXTURE_CUBE_MAP);
+        texture.bind();
+        texture.set_int_parameter(gl::TEXTURE_MIN_FILTER, gl::LINEAR as gl::types::GLint);
+        texture.set_int_parameter(gl::TEXTURE_MAG_FILTER, gl::LINEAR as gl::types::GLint);
+        texture.set_int_parameter(gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as gl::types::GLint);
+        texture.set_int_parameter(gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as gl::types::GLint);
+        texture.set_int_parameter(gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as gl::types::GLint);
+
+        Cubemap {
+            texture
+        }
+    }
+
+    /// Sets the RGBA pixels of a face, given by its target such as
+    /// gl::TEXTURE_CUBE_MAP_POSITIVE_X, the cubemap must be bound
+    ///
+    /// The faces must all be square and of the same size.
+    pub fn set_face_data(&self, face: gl::types::GLenum,
+                         width: gl::types::GLsizei, height: gl::types::GLsizei,
+                         data: &[u8]) {
+        assert!(Cubemap::FACES.contains(&face), "{:#x} isn't a cubemap face", face);
+        assert_eq!(data.len(), (width * height * 4) as usize,
+                   "The face data doesn't match the face size");
+
+        unsafe {
+            gl::TexImage2D(face,
+                           0,
+                           gl::RGBA8 as gl::types::GLint,
+                           width,
+                           height,
+                           0,
+                           gl::RGBA,
+                           gl::UNSIGNED_BYTE,
+                           data.as_ptr() as *const gl::types::GLvoid);
+        }
+        debug_check("glTexImage2D");
+        self.texture.dimensions.set((width, height));
+        self.texture.format.set(gl::RGBA);
+    }
+}
+
+impl Default for Cubemap {
+    fn default() -> Cubemap {
+        Cubemap::new()
+    }
+}
+
+impl std::ops::Deref for Cubemap {
+    type Target = Texture;
+
+    fn deref(&self) -> &Texture {
+        &self.texture
+    }
+}
+
+impl Drop for Cubemap {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteTextures(1, &self.texture.identifier()); }
+    }
+}
+
+/// DXT1 internal format of GL_EXT_texture_compression_s3tc, which the
+/// core bindings don't define
+pub const COMPRESSED_RGBA_S3TC_DXT1: gl::types::GLenum = 0x83F1;
+/// DXT3 internal format of GL_EXT_texture_compression_s3tc
+pub const COMPRESSED_RGBA_S3TC_DXT3: gl::types::GLenum = 0x83F2;
+/// DXT5 internal format of GL_EXT_texture_compression_s3tc
+pub const COMPRESSED_RGBA_S3TC_DXT5: gl::types::GLenum = 0x83F3;
+
+/// Anisotropy texture parameter of GL_EXT_texture_filter_anisotropic,
+/// which the core bindings don't define
+pub const TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FE;
+/// Largest anisotropy supported by the context
+pub const MAX_TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FF;
+
+/// Compressed image with its mipmap chain, ready to be uploaded to a
+/// texture
+///
+/// Like `DecodedImage`, it doesn't hold any OpenGL object.
+pub struct CompressedImage {
+    width: gl::types::GLsizei,
+    height: gl::types::GLsizei,
+    internal_format: gl::types::GLenum,
+    levels: Vec<Vec<u8>>
+}
+
+impl CompressedImage {
+    /// Creates a compressed image from the data of its levels, the base
+    /// level first
+    pub fn new(width: gl::types::GLsizei,
+               height: gl::types::GLsizei,
+               internal_format: gl::types::GLenum,
+               levels: Vec<Vec<u8>>) -> CompressedImage {
+        CompressedImage {
+            width,
+            height,
+            internal_format,
+            levels
+        }
+    }
+
+    pub fn width(&self) -> gl::types::GLsizei {
+        self.width
+    }
+
+    pub fn height(&self) -> gl::types::GLsizei {
+        self.height
+    }
+
+    pub fn internal_format(&self) -> gl::types::GLenum {
+        self.internal_format
+    }
+
+    pub fn levels(&self) -> &[Vec<u8>] {
+        &self.levels
+    }
+}
+
+/// Decoded image, ready to be uploaded to a texture
+///
+/// It doesn't hold any OpenGL object, so images can be decoded on worker
+/// threads and sent to the thread owning the OpenGL context for upload.
+pub struct DecodedImage {
+    width: gl::types::GLsizei,
+    height: gl::types::GLsizei,
+    format: gl::types::GLenum,
+    pixels: Vec<u8>
+}
+
+impl DecodedImage {
+    /// Creates an image from unsigned byte pixels of the given format,
+    /// with rows ordered from bottom to top
+    pub fn new(width: gl::types::GLsizei,
+               height: gl::types::GLsizei,
+               format: gl::types::GLenum,
+               pixels: Vec<u8>) -> DecodedImage {
+        DecodedImage {
+            width,
+            height,
+            format,
+            pixels
+        }
+    }
+
+    pub fn width(&self) -> gl::types::GLsizei {
+        self.width
+    }
+
+    pub fn height(&self) -> gl::types::GLsizei {
+        self.height
+    }
+
+    pub fn format(&self) -> gl::types::GLenum {
+        self.format
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Multiplies the color channels of an RGBA image by its alpha channel
+    ///
+    /// Images of other formats are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::opengl::DecodedImage;
+    ///
+    /// let mut image = DecodedImage::new(1, 1, gl::RGBA, vec!(255, 128, 0, 128));
+    /// image.premultiply_alpha();
+    /// assert_eq!(image.pixels(), &[128, 64, 0, 128]);
+    /// ```
+    pub fn premultiply_alpha(&mut self) {
+        if self.format != gl::RGBA {
+            return;
+        }
+
+        for pixel in self.pixels.chunks_mut(4) {
+            let alpha = pixel[3] as u32;
+            for channel in pixel[..3].iter_mut() {
+                *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+            }
+        }
+    }
+}
+
+/// Sampling parameters applied to a texture after its image data is set
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::opengl::TextureParameters;
+///
+/// let parameters = TextureParameters::new().mipmaps(true);
+/// assert_eq!(parameters.effective_min_filter(), gl::LINEAR_MIPMAP_LINEAR);
+///
+/// let parameters = TextureParameters::new();
+/// assert_eq!(parameters.effective_min_filter(), gl::NEAREST);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct TextureParameters {
+    min_filter: Option<gl::types::GLenum>,
+    mag_filter: gl::types::GLenum,
+    wrap_s: gl::types::GLenum,
+    wrap_t: gl::types::GLenum,
+    mipmaps: bool,
+    premultiply: bool,
+    border_color: [gl::types::GLfloat; 4],
+    level_range: Option<(gl::types::GLint, gl::types::GLint)>,
+    lod_range: Option<(gl::types::GLfloat, gl::types::GLfloat)>
+}
+
+impl TextureParameters {
+    /// Creates parameters using nearest filtering, repeat wrapping and no
+    /// mipmaps
+    pub fn new() -> TextureParameters {
+        TextureParameters {
+            min_filter: None,
+            mag_filter: gl::NEAREST,
+            wrap_s: gl::REPEAT,
+            wrap_t: gl::REPEAT,
+            mipmaps: false,
+            premultiply: false,
+            border_color: [0.0, 0.0, 0.0, 0.0],
+            level_range: None,
+            lod_range: None
+        }
+    }
+
+    /// Creates parameters for pixel art, sampled pixel perfect
+    ///
+    /// Filtering is nearest and both coordinates are clamped to the edges,
+    /// so neighbouring cells of a sprite sheet don't bleed into each other.
+    /// Enabling mipmaps later still generates them, but the nearest
+    /// minification filter never samples them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::opengl::TextureParameters;
+    ///
+    /// let parameters = TextureParameters::pixel_art().mipmaps(true);
+    /// assert_eq!(parameters.effective_min_filter(), gl::NEAREST);
+    /// ```
+    pub fn pixel_art() -> TextureParameters {
+        TextureParameters::new()
+            .min_filter(gl::NEAREST)
+            .mag_filter(gl::NEAREST)
+            .wrap(gl::CLAMP_TO_EDGE)
+    }
+
+    pub fn min_filter(mut self, min_filter: gl::types::GLenum) -> TextureParameters {
+        self.min_filter = Some(min_filter);
+        self
+    }
+
+    pub fn mag_filter(mut self, mag_filter: gl::types::GLenum) -> TextureParameters {
+        self.mag_filter = mag_filter;
+        self
+    }
+
+    pub fn wrap_s(mut self, wrap_s: gl::types::GLenum) -> TextureParameters {
+        self.wrap_s = wrap_s;
+        self
+    }
+
+    pub fn wrap_t(mut self, wrap_t: gl::types::GLenum) -> TextureParameters {
+        self.wrap_t = wrap_t;
+        self
+    }
+
+    /// Sets the wrapping of both texture coordinates
+    pub fn wrap(mut self, wrap: gl::types::GLenum) -> TextureParameters {
+        self.wrap_s = wrap;
+        self.wrap_t = wrap;
+        self
+    }
+
+    /// Clamps both coordinates to a border of the given RGBA color
+    ///
+    /// Coordinates outside of the texture sample the border color instead
+    /// of repeating the texture or stretching its edges.
+    pub fn clamp_to_border(mut self, border_color: [gl::types::GLfloat; 4])
+        -> TextureParameters {
+        self.wrap_s = gl::CLAMP_TO_BORDER;
+        self.wrap_t = gl::CLAMP_TO_BORDER;
+        self.border_color = border_color;
+        self
+    }
+
+    /// Sets whether mipmaps are generated
+    ///
+    /// Unless a minifying filter is set explicitly, gl::LINEAR_MIPMAP_LINEAR
+    /// is used when mipmaps are generated so that they are sampled.
+    pub fn mipmaps(mut self, mipmaps: bool) -> TextureParameters {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    /// Restricts the mipmap levels that can be sampled to the given
+    /// range, bounds included
+    ///
+    /// This allows to sample a texture whose level 0 hasn't been uploaded
+    /// yet by setting the base level to the largest level available.
+    pub fn level_range(mut self, base_level: gl::types::GLint,
+                       max_level: gl::types::GLint) -> TextureParameters {
+        self.level_range = Some((base_level, max_level));
+        self
+    }
+
+    /// Clamps the level of detail computed when sampling to the given range
+    pub fn lod_range(mut self, min_lod: gl::types::GLfloat,
+                     max_lod: gl::types::GLfloat) -> TextureParameters {
+        self.lod_range = Some((min_lod, max_lod));
+        self
+    }
+
+    /// Sets whether the color channels of RGBA images are multiplied by
+    /// their alpha when uploaded with `Texture::from_image`
+    ///
+    /// Premultiplied textures must be drawn with
+    /// `BlendMode::PremultipliedAlpha`, straight alpha textures with
+    /// `BlendMode::Alpha`. Premultiplying avoids dark fringes around
+    /// transparent edges when the texture is filtered.
+    pub fn premultiply(mut self, premultiply: bool) -> TextureParameters {
+        self.premultiply = premultiply;
+        self
+    }
+
+    pub fn premultiplies(&self) -> bool {
+        self.premultiply
+    }
+
+    /// Returns the minifying filter applied to the texture
+    pub fn effective_min_filter(&self) -> gl::types::GLenum {
+        match self.min_filter {
+            Some(min_filter) => min_filter,
+            None if self.mipmaps => gl::LINEAR_MIPMAP_LINEAR,
+            None => gl::NEAREST
+        }
+    }
+
+    /// Generates the mipmaps if needed and sets the parameters of the
+    /// texture, leaving it bound
+    pub fn apply(&self, texture: &Texture) {
+        texture.bind();
+        if self.mipmaps {
+            texture.generate_mipmap();
+        }
+
+        texture.set_int_parameter(gl::TEXTURE_MIN_FILTER,
+                                  self.effective_min_filter() as gl::types::GLint);
+        texture.set_int_parameter(gl::TEXTURE_MAG_FILTER,
+                                  self.mag_filter as gl::types::GLint);
+        texture.set_int_parameter(gl::TEXTURE_WRAP_S,
+                                  self.wrap_s as gl::types::GLint);
+        texture.set_int_parameter(gl::TEXTURE_WRAP_T,
+                                  self.wrap_t as gl::types::GLint);
+        if self.wrap_s == gl::CLAMP_TO_BORDER || self.wrap_t == gl::CLAMP_TO_BORDER {
+            texture.set_float_array_parameter(gl::TEXTURE_BORDER_COLOR,
+                                              &self.border_color);
+        }
+        if let Some((base_level, max_level)) = self.level_range {
+            texture.set_int_parameter(gl::TEXTURE_BASE_LEVEL, base_level);
+            texture.set_int_parameter(gl::TEXTURE_MAX_LEVEL, max_level);
+        }
+        if let Some((min_lod, max_lod)) = self.lod_range {
+            texture.set_float_parameter(gl::TEXTURE_MIN_LOD, min_lod);
+            texture.set_float_parameter(gl::TEXTURE_MAX_LOD, max_lod);
+        }
+    }
+}
+
+impl Default for TextureParameters {
+    fn default() -> TextureParameters {
+        TextureParameters::new()
+    }
 }
@@ -0,0 +1,155 @@
+/*
+* MIT License
+*
+* Copyright (c) 2019 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+
+//! This module contains the description of the passes rendering a scene
+//! graph, see `GLSceneRenderer::render_passes`
+
+use tuber::scene::SceneNode;
+
+use crate::RGBColor;
+
+/// Framebuffer a pass draws into
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RenderTarget {
+    /// The default framebuffer, usually the window
+    Default,
+    /// A framebuffer object, given by its OpenGL identifier as returned
+    /// by `opengl::Framebuffer::identifier`
+    Framebuffer(gl::types::GLuint)
+}
+
+impl RenderTarget {
+    pub fn framebuffer_identifier(&self) -> gl::types::GLuint {
+        match self {
+            RenderTarget::Default => 0,
+            RenderTarget::Framebuffer(identifier) => *identifier
+        }
+    }
+}
+
+/// What a pass clears before drawing
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClearPolicy {
+    /// Draws over the content of the target
+    Keep,
+    /// Clears the color buffer with a color
+    Color(RGBColor),
+    /// Clears the color buffer with a color and the depth buffer
+    ColorAndDepth(RGBColor)
+}
+
+/// Pass rendering the nodes of a scene graph into a target
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::GLSceneRenderer;
+/// use tuber_graphics_opengl::render_pass::{ClearPolicy, RenderPass, RenderTarget};
+/// use tuber::scene::SceneNode;
+///
+/// let projection = GLSceneRenderer::screen_ortho(800.0, 600.0);
+/// let ui_pass = RenderPass::new(projection)
+///     .clear(ClearPolicy::Keep)
+///     .filter(Box::new(|node: &SceneNode| node.identifier().starts_with("ui_")));
+/// assert_eq!(ui_pass.render_target(), RenderTarget::Default);
+/// assert_eq!(ui_pass.clear_policy(), ClearPolicy::Keep);
+/// ```
+pub struct RenderPass {
+    target: RenderTarget,
+    clear: ClearPolicy,
+    projection: nalgebra_glm::Mat4,
+    viewport: Option<(i32, i32, i32, i32)>,
+    shader: Option<String>,
+    filter: Option<Box<dyn Fn(&SceneNode) -> bool>>
+}
+
+impl RenderPass {
+    /// Creates a pass drawing every node into the default framebuffer,
+    /// cleared in black
+    pub fn new(projection: nalgebra_glm::Mat4) -> RenderPass {
+        RenderPass {
+            target: RenderTarget::Default,
+            clear: ClearPolicy::Color((0.0, 0.0, 0.0)),
+            projection,
+            viewport: None,
+            shader: None,
+            filter: None
+        }
+    }
+
+    pub fn target(mut self, target: RenderTarget) -> RenderPass {
+        self.target = target;
+        self
+    }
+
+    pub fn clear(mut self, clear_policy: ClearPolicy) -> RenderPass {
+        self.clear = clear_policy;
+        self
+    }
+
+    /// Sets the viewport of the pass, the viewport of the renderer is
+    /// kept otherwise
+    pub fn viewport(mut self, x: i32, y: i32, width: i32, height: i32) -> RenderPass {
+        self.viewport = Some((x, y, width, height));
+        self
+    }
+
+    /// Draws all the meshes of the pass with a program of the shader store
+    pub fn shader(mut self, shader_identifier: &str) -> RenderPass {
+        self.shader = Some(shader_identifier.into());
+        self
+    }
+
+    /// Only draws the nodes for which the filter returns true
+    pub fn filter(mut self, filter: Box<dyn Fn(&SceneNode) -> bool>) -> RenderPass {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn render_target(&self) -> RenderTarget {
+        self.target
+    }
+
+    pub fn clear_policy(&self) -> ClearPolicy {
+        self.clear
+    }
+
+    pub fn projection(&self) -> &nalgebra_glm::Mat4 {
+        &self.projection
+    }
+
+    pub fn viewport_rect(&self) -> Option<(i32, i32, i32, i32)> {
+        self.viewport
+    }
+
+    pub fn shader_identifier(&self) -> &Option<String> {
+        &self.shader
+    }
+
+    /// Returns whether the pass draws the given node
+    pub fn accepts(&self, node: &SceneNode) -> bool {
+        self.filter.as_ref().map_or(true, |filter| filter(node))
+    }
+}
@@ -0,0 +1,131 @@
+/*
+* MIT License
+*
+* Copyright (c) 2019 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+//! This module contains the resource store and loader for shader programs
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use tuber::resources::{ResourceLoader, ResourceStore};
+
+pub use crate::opengl::{Shader, ShaderProgram};
+
+/// Store of shader programs
+pub struct ShaderStore {
+    shader_programs: HashMap<String, ShaderProgram>
+}
+
+impl ShaderStore {
+    pub fn new() -> ShaderStore {
+        ShaderStore {
+            shader_programs: HashMap::new()
+        }
+    }
+}
+
+impl Default for ShaderStore {
+    fn default() -> ShaderStore {
+        ShaderStore::new()
+    }
+}
+
+impl ResourceStore<ShaderProgram> for ShaderStore {
+    fn store(&mut self, resource_file_path: String, value: ShaderProgram) {
+        self.shader_programs.insert(resource_file_path, value);
+    }
+    fn remove(&mut self, resource_file_path: &str) {
+        self.shader_programs.remove(resource_file_path);
+    }
+
+    fn get(&self, resource_file_path: &str) -> Option<&ShaderProgram> {
+        self.shader_programs.get(resource_file_path)
+    }
+    fn get_mut(&mut self, resource_file_path: &str) -> Option<&mut ShaderProgram> {
+        self.shader_programs.get_mut(resource_file_path)
+    }
+}
+
+/// Loader of shader programs described by resource files
+///
+/// A resource `name` is described by the file `name.jbb` of the resource
+/// directory, which gives the paths of the shader sources relative to
+/// that directory:
+///
+/// ```json
+/// {
+///     "vertex_shader": "textured.vert",
+///     "fragment_shader": "textured.frag"
+/// }
+/// ```
+pub struct ShaderLoader {
+    resource_directory: PathBuf
+}
+
+impl ShaderLoader {
+    /// Creates a loader reading resource files from the given directory
+    pub fn new<P: AsRef<Path>>(resource_directory: P) -> ShaderLoader {
+        ShaderLoader {
+            resource_directory: resource_directory.as_ref().to_path_buf()
+        }
+    }
+
+    /// Reads the resource file of a shader program
+    fn read_resource_file(&self, resource_file_path: &str)
+        -> Result<serde_json::Value, String> {
+        use std::fs::File;
+        use std::io::{BufReader, Read};
+
+        let file_path = self.resource_directory
+            .join(resource_file_path.to_owned() + ".jbb");
+        let file = File::open(&file_path)
+            .map_err(|e| format!("Can't open resource file {}: {}",
+                                 file_path.display(), e))?;
+        let mut contents = String::new();
+        BufReader::new(file).read_to_string(&mut contents)
+            .map_err(|e| format!("Can't read resource file {}: {}",
+                                 file_path.display(), e))?;
+
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Can't parse resource file {}: {}",
+                                 file_path.display(), e))
+    }
+}
+
+impl ResourceLoader<ShaderProgram> for ShaderLoader {
+    fn load(&mut self, resource_file_path: &str) -> Result<ShaderProgram, String> {
+        let resource = self.read_resource_file(resource_file_path)?;
+
+        let mut shaders = vec!();
+        for (key, kind) in [("vertex_shader", gl::VERTEX_SHADER),
+                            ("fragment_shader", gl::FRAGMENT_SHADER)].iter() {
+            let source_file = resource[*key].as_str()
+                .ok_or(format!("Missing {} in resource file of {}",
+                               key, resource_file_path))?;
+            shaders.push(Shader::from_file(&self.resource_directory.join(source_file),
+                                           *kind)?);
+        }
+
+        ShaderProgram::from_shaders(&shaders)
+    }
+}
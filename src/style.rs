@@ -0,0 +1,463 @@
+/*
+* MIT License
+*
+* Copyright (c) 2019 Clément SIBILLE
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+//! This module contains rendering options attached to scene nodes
+//!
+//! Styles are registered on the renderer with the identifier of the node
+//! they apply to. The background is set on the renderer directly.
+
+use std::ops::Range;
+
+/// Normalized sub-rectangle of a texture, given as (u, v, width, height)
+pub type TextureRegion = (f32, f32, f32, f32);
+
+/// Background drawn over the whole viewport before the scene
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Background {
+    Color((f32, f32, f32)),
+    /// Gradient from the top to the bottom of the viewport
+    VerticalGradient {
+        top: (f32, f32, f32),
+        bottom: (f32, f32, f32)
+    },
+    /// Gradient from the center to the corners of the viewport
+    RadialGradient {
+        center: (f32, f32, f32),
+        edge: (f32, f32, f32)
+    }
+}
+
+/// Rendering style of a line node
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LineStyle {
+    Solid,
+    /// Dashes and gaps between them, their lengths are in pixels
+    Dashed {
+        dash: f32,
+        gap: f32
+    }
+}
+
+impl Default for LineStyle {
+    fn default() -> LineStyle {
+        LineStyle::Solid
+    }
+}
+
+/// Rendering options of a rectangle node
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::style::RectangleStyle;
+///
+/// let style = RectangleStyle::new()
+///     .texture("background")
+///     .texture_region((0.0, 0.0, 0.5, 0.5));
+/// assert_eq!(style.texture_identifier(), &Some("background".to_string()));
+/// ```
+#[derive(Clone, Debug)]
+pub struct RectangleStyle {
+    texture_identifier: Option<String>,
+    texture_region: TextureRegion,
+    filled: bool,
+    stroke_width: f32,
+    corner_radius: f32,
+    corner_segments: u32
+}
+
+impl RectangleStyle {
+    pub fn new() -> RectangleStyle {
+        RectangleStyle {
+            texture_identifier: None,
+            texture_region: (0.0, 0.0, 1.0, 1.0),
+            filled: true,
+            stroke_width: 0.0,
+            corner_radius: 0.0,
+            corner_segments: 0
+        }
+    }
+
+    /// Rounds the corners of a filled rectangle
+    ///
+    /// Each corner is approximated by `segments` segments. The radius is
+    /// clamped to half the smallest side of the rectangle.
+    pub fn rounded_corners(mut self, radius: f32, segments: u32) -> RectangleStyle {
+        self.corner_radius = radius;
+        self.corner_segments = segments;
+        self
+    }
+
+    /// Sets whether the rectangle is filled or only outlined
+    pub fn filled(mut self, filled: bool) -> RectangleStyle {
+        self.filled = filled;
+        self
+    }
+
+    /// Sets the width of the outline of an unfilled rectangle
+    ///
+    /// The outline is drawn inside the rectangle. A width of 0 draws it
+    /// with lines.
+    pub fn stroke_width(mut self, stroke_width: f32) -> RectangleStyle {
+        self.stroke_width = stroke_width;
+        self
+    }
+
+    /// Sets the texture drawn on the rectangle, tinted by its color
+    pub fn texture(mut self, texture_identifier: &str) -> RectangleStyle {
+        self.texture_identifier = Some(texture_identifier.into());
+        self
+    }
+
+    /// Sets the region of the texture mapped onto the rectangle
+    pub fn texture_region(mut self, texture_region: TextureRegion)
+        -> RectangleStyle {
+        self.texture_region = texture_region;
+        self
+    }
+
+    pub fn texture_identifier(&self) -> &Option<String> {
+        &self.texture_identifier
+    }
+
+    pub fn region(&self) -> TextureRegion {
+        self.texture_region
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.filled
+    }
+
+    pub fn stroke_thickness(&self) -> f32 {
+        self.stroke_width
+    }
+
+    pub fn corner_radius(&self) -> f32 {
+        self.corner_radius
+    }
+
+    pub fn corner_segments(&self) -> u32 {
+        self.corner_segments
+    }
+}
+
+impl Default for RectangleStyle {
+    fn default() -> RectangleStyle {
+        RectangleStyle::new()
+    }
+}
+
+/// Rotation and scale applied to a sprite node around a pivot
+///
+/// The rotation is in radians and turns the X axis towards the Y axis,
+/// which is clockwise on screen with `GLSceneRenderer::screen_ortho`. The
+/// pivot is relative to the top-left corner of the sprite and defaults to
+/// its center.
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::style::SpriteTransform;
+///
+/// let transform = SpriteTransform::new()
+///     .rotation(std::f32::consts::FRAC_PI_2);
+/// let corners = [(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0)];
+/// let expected = [(2.0, 0.0), (0.0, 0.0), (0.0, 2.0), (2.0, 2.0)];
+/// for (corner, expected) in corners.iter().zip(expected.iter()) {
+///     let (x, y) = transform.apply(*corner, (2.0, 2.0));
+///     assert!((x - expected.0).abs() < 1e-5 && (y - expected.1).abs() < 1e-5);
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpriteTransform {
+    rotation: f32,
+    scale: (f32, f32),
+    pivot: Option<(f32, f32)>
+}
+
+impl SpriteTransform {
+    pub fn new() -> SpriteTransform {
+        SpriteTransform {
+            rotation: 0.0,
+            scale: (1.0, 1.0),
+            pivot: None
+        }
+    }
+
+    pub fn rotation(mut self, rotation: f32) -> SpriteTransform {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn scale(mut self, scale: (f32, f32)) -> SpriteTransform {
+        self.scale = scale;
+        self
+    }
+
+    pub fn pivot(mut self, pivot: (f32, f32)) -> SpriteTransform {
+        self.pivot = Some(pivot);
+        self
+    }
+
+    pub fn rotation_angle(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn scale_factors(&self) -> (f32, f32) {
+        self.scale
+    }
+
+    /// Returns the pivot of a sprite of the given size
+    pub fn pivot_point(&self, size: (f32, f32)) -> (f32, f32) {
+        self.pivot.unwrap_or((size.0 / 2.0, size.1 / 2.0))
+    }
+
+    /// Transforms a point of a sprite of the given size
+    pub fn apply(&self, point: (f32, f32), size: (f32, f32)) -> (f32, f32) {
+        let pivot = self.pivot_point(size);
+        let x = (point.0 - pivot.0) * self.scale.0;
+        let y = (point.1 - pivot.1) * self.scale.1;
+        let (sin, cos) = self.rotation.sin_cos();
+        (pivot.0 + x * cos - y * sin, pivot.1 + x * sin + y * cos)
+    }
+}
+
+impl Default for SpriteTransform {
+    fn default() -> SpriteTransform {
+        SpriteTransform::new()
+    }
+}
+
+/// Position, rotation and scale of a node
+///
+/// The node is scaled, then rotated around its origin, then translated.
+///
+/// # Examples
+///
+/// ```
+/// use tuber_graphics_opengl::style::NodeTransform;
+///
+/// let transform = NodeTransform::new()
+///     .translation((10.0, 20.0))
+///     .scale((2.0, 2.0));
+/// let point = transform.matrix() * nalgebra_glm::vec4(1.0, 1.0, 0.0, 1.0);
+/// assert_eq!((point.x, point.y), (12.0, 22.0));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NodeTransform {
+    translation: (f32, f32),
+    rotation: f32,
+    scale: (f32, f32)
+}
+
+impl NodeTransform {
+    pub fn new() -> NodeTransform {
+        NodeTransform {
+            translation: (0.0, 0.0),
+            rotation: 0.0,
+            scale: (1.0, 1.0)
+        }
+    }
+
+    pub fn translation(mut self, translation: (f32, f32)) -> NodeTransform {
+        self.translation = translation;
+        self
+    }
+
+    /// Sets the rotation, in radians
+    pub fn rotation(mut self, rotation: f32) -> NodeTransform {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn scale(mut self, scale: (f32, f32)) -> NodeTransform {
+        self.scale = scale;
+        self
+    }
+
+    pub fn translation_offset(&self) -> (f32, f32) {
+        self.translation
+    }
+
+    pub fn rotation_angle(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn scale_factors(&self) -> (f32, f32) {
+        self.scale
+    }
+
+    /// Returns the matrix transforming the node space into its parent's
+    pub fn matrix(&self) -> nalgebra_glm::Mat4 {
+        let translation = nalgebra_glm::translation(
+            &nalgebra_glm::vec3(self.translation.0, self.translation.1, 0.0));
+        let rotation = nalgebra_glm::rotation(self.rotation,
+                                              &nalgebra_glm::vec3(0.0, 0.0, 1.0));
+        let scale = nalgebra_glm::scaling(&nalgebra_glm::vec3(self.scale.0, self.scale.1, 1.0));
+        translation * rotation * scale
+    }
+}
+
+impl Default for NodeTransform {
+    fn default() -> NodeTransform {
+        NodeTransform::new()
+    }
+}
+
+/// Vertical position of a text node's origin relative to its line
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum VerticalAnchor {
+    /// The origin is at the top of the line
+    Top,
+    /// The origin is on the baseline of the line
+    Baseline,
+    /// The origin is at the bottom of the line
+    Bottom
+}
+
+impl Default for VerticalAnchor {
+    fn default() -> VerticalAnchor {
+        VerticalAnchor::Top
+    }
+}
+
+/// Direction the characters of a text node are laid out in
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TextDirection {
+    /// The origin is the left edge of the text
+    LeftToRight,
+    /// The origin is the right edge of the text, the characters are
+    /// expected to be shaped already
+    RightToLeft
+}
+
+impl Default for TextDirection {
+    fn default() -> TextDirection {
+        TextDirection::LeftToRight
+    }
+}
+
+/// Rendering options of a text node
+#[derive(Clone, Debug, Default)]
+pub struct TextStyle {
+    runs: Vec<TextRun>,
+    vertical_anchor: VerticalAnchor,
+    direction: TextDirection
+}
+
+impl TextStyle {
+    pub fn new() -> TextStyle {
+        TextStyle {
+            runs: vec!(),
+            vertical_anchor: VerticalAnchor::Top,
+            direction: TextDirection::LeftToRight
+        }
+    }
+
+    /// Sets the direction the characters are laid out in
+    pub fn direction(mut self, direction: TextDirection) -> TextStyle {
+        self.direction = direction;
+        self
+    }
+
+    pub fn text_direction(&self) -> TextDirection {
+        self.direction
+    }
+
+    /// Sets where the origin of the text is placed vertically
+    ///
+    /// Glyphs are laid out with the y axis pointing down, the default
+    /// anchor places the top of the line at the origin.
+    pub fn vertical_anchor(mut self, vertical_anchor: VerticalAnchor)
+        -> TextStyle {
+        self.vertical_anchor = vertical_anchor;
+        self
+    }
+
+    pub fn anchor(&self) -> VerticalAnchor {
+        self.vertical_anchor
+    }
+
+    /// Adds a run of characters drawn at the given scale, with their
+    /// baseline raised by `baseline_shift` pixels
+    ///
+    /// The range is expressed in characters. When runs overlap, the last
+    /// added one applies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tuber_graphics_opengl::style::TextStyle;
+    ///
+    /// // "H2O" with a subscript 2
+    /// let style = TextStyle::new().run(1..2, 0.5, -4.0);
+    /// assert_eq!(style.scale_and_shift(0), (1.0, 0.0));
+    /// assert_eq!(style.scale_and_shift(1), (0.5, -4.0));
+    /// assert_eq!(style.scale_and_shift(2), (1.0, 0.0));
+    /// ```
+    pub fn run(mut self, range: Range<usize>,
+               scale: f32, baseline_shift: f32) -> TextStyle {
+        self.runs.push(TextRun {
+            range,
+            scale,
+            baseline_shift
+        });
+        self
+    }
+
+    pub fn runs(&self) -> &Vec<TextRun> {
+        &self.runs
+    }
+
+    /// Returns the scale and baseline shift of the character at the given
+    /// index
+    pub fn scale_and_shift(&self, character_index: usize) -> (f32, f32) {
+        self.runs.iter()
+            .rev()
+            .find(|run| run.range.contains(&character_index))
+            .map_or((1.0, 0.0), |run| (run.scale, run.baseline_shift))
+    }
+}
+
+/// Range of characters of a text with a specific scale and baseline
+#[derive(Clone, Debug)]
+pub struct TextRun {
+    range: Range<usize>,
+    scale: f32,
+    baseline_shift: f32
+}
+
+impl TextRun {
+    pub fn range(&self) -> &Range<usize> {
+        &self.range
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn baseline_shift(&self) -> f32 {
+        self.baseline_shift
+    }
+}